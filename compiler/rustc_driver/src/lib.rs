@@ -18,6 +18,7 @@
 use rustc_ast as ast;
 use rustc_codegen_ssa::{traits::CodegenBackend, CodegenResults};
 use rustc_data_structures::profiling::{get_resident_set_size, print_time_passes_entry};
+use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::SeqCst;
 use rustc_errors::registry::{InvalidErrorCode, Registry};
 use rustc_errors::{ErrorGuaranteed, PResult};
@@ -27,7 +28,7 @@
 use rustc_lint::LintStore;
 use rustc_log::stdout_isatty;
 use rustc_metadata::locator;
-use rustc_middle::ty::{self, TyCtxt};
+use rustc_middle::ty::TyCtxt;
 use rustc_save_analysis as save;
 use rustc_save_analysis::DumpHandler;
 use rustc_session::config::{nightly_options, CG_OPTIONS, DB_OPTIONS};
@@ -37,7 +38,7 @@
 use rustc_session::lint::{Lint, LintId};
 use rustc_session::{config, DiagnosticOutput, Session};
 use rustc_session::{early_error, early_error_no_abort, early_warn};
-use rustc_span::def_id::{CrateNum, DefId};
+use rustc_span::def_id::CrateNum;
 use rustc_span::source_map::{FileLoader, FileName};
 use rustc_span::symbol::sym;
 use rustc_target::json::ToJson;
@@ -56,9 +57,13 @@
 use std::str;
 use std::time::Instant;
 
-mod data_wrapper;
-use data_wrapper::{get_bb_refed_strs, MirBasicBlock};
+use mir_dump::{
+    body_to_dot, extract_crate, file_stem_of_key, key_of_file_stem, CrateDump, ExtractConfig,
+    ExtractPhase,
+};
+
 pub mod args;
+pub mod mir_dump;
 pub mod pretty;
 
 /// Exit status code used for successful compilation and help output.
@@ -1340,56 +1345,178 @@ pub fn main() -> ! {
     process::exit(exit_code)
 }
 
+// Whether RUSTC_MIR_DUMP_CRATES (colon separated) selects the local crate.
+fn is_dump_crate(tcx: TyCtxt<'_>) -> bool {
+    let local_crate = tcx.crate_name(CrateNum::from_u32(0)).to_ident_string();
+    env::var("RUSTC_MIR_DUMP_CRATES")
+        .unwrap_or("".to_string())
+        .split(":")
+        .any(|x| x == local_crate)
+}
+
 // Self defined callbacks
 #[derive(Default)]
 pub struct SigGenCallback {
-    all_mirs: Vec<(String, Vec<(u32, MirBasicBlock)>)>,
+    config: ExtractConfig,
+    dump: CrateDump,
+    // The dump directory and the `{crate}_{version}` stem of the dump files, set
+    // by `prepare_dump` when the local crate is to be dumped.
+    target: Option<(PathBuf, String)>,
 }
 
 impl SigGenCallback {
-    fn record_mir<'tcx>(
-        &mut self,
-        mir: &'tcx rustc_middle::mir::Body<'tcx>,
-        key: DefId,
-        tcx: TyCtxt<'tcx>,
-    ) {
-        let promoteds = tcx.promoted_mir(&key);
-        let bbs: Vec<(u32, MirBasicBlock)> = mir
-            .basic_blocks()
-            .iter_enumerated()
-            .map(|(bb_idx, bb)| {
-                let ref_strs = get_bb_refed_strs(tcx, &bb, &promoteds);
-                let is_cleanup = bb.is_cleanup;
-                let term = &bb.terminator().kind;
-                let statements = bb
-                    .statements
-                    .iter()
-                    .map(|x| (&x.kind).into())
-                    .collect::<Vec<_>>();
-                // .map(|x| format!("{:?}", x))
-                // .collect::<Vec<_>>();
-                (
-                    bb_idx.as_u32(),
-                    MirBasicBlock::new(statements, term.into(), is_cleanup, ref_strs),
-                )
-            })
-            .collect::<Vec<_>>();
-        self.all_mirs.push((format!("{:?}", tcx.type_of(key)), bbs));
+    fn dump_mir(&mut self, file_path: &PathBuf) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(file_path)
+            .expect(&format!("Failed to create file {:?}.", file_path));
+        let mut write = || {
+            file.write_all(serde_json::to_string(&self.dump).unwrap().as_bytes()).unwrap()
+        };
+        match &self.config.timings {
+            Some(timings) => timings.time(|t| &t.serialization, write),
+            None => write(),
+        }
+        self.dump.bodies.clear();
     }
 
-    fn dump_mir(&mut self, file_path: &PathBuf) {
+    // Write every body to its own file in `item_dir`, named like the items of
+    // the manifest, which serves as the index of the files and maps each file
+    // to the `MirDefId::key` of its body.
+    fn dump_items(&mut self, item_dir: &PathBuf) {
+        if !item_dir.exists() && fs::create_dir_all(item_dir).is_err() {
+            eprintln!("Failed to create dir {:?}.", item_dir);
+            return;
+        }
+        for (body, (_, key, _)) in self.dump.bodies.iter().zip(&self.dump.items) {
+            let mut file_path = item_dir.clone();
+            file_path.push(format!("{}.json", file_stem_of_key(key)));
+            let write = || serde_json::to_string(body).unwrap();
+            let json = match &self.config.timings {
+                Some(timings) => timings.time(|t| &t.serialization, write),
+                None => write(),
+            };
+            fs::write(&file_path, json).expect(&format!("Failed to write file {:?}.", file_path));
+        }
+        self.dump.bodies.clear();
+    }
+
+    // Checks the dump directory before anything is extracted, and sets `target`
+    // if the local crate is to be dumped: it is selected and, unless
+    // RUSTC_MIR_OVERWRITE is set, was not dumped before. Returns false when
+    // RUSTC_MIR_DUMP_DIR is not usable and compilation should stop.
+    fn prepare_dump(&mut self, tcx: TyCtxt<'_>) -> bool {
+        // Prepare dump dir.
+        let dump_dir = PathBuf::from(env::var("RUSTC_MIR_DUMP_DIR").unwrap_or("".to_string()));
+        if !dump_dir.is_absolute() {
+            println!("Please set RUSTC_MIR_DUMP_DIR with an absolute path.");
+            println!("Compilation stoped.");
+            return false;
+        }
+        if !dump_dir.exists() && fs::create_dir_all(&dump_dir).is_err() {
+            println!("Failed to create dir {:?}.", dump_dir);
+        }
+        // Match crate
+        if !is_dump_crate(tcx) {
+            return true;
+        }
+        let local_crate = tcx.crate_name(CrateNum::from_u32(0)).to_ident_string();
+        let crate_version = env::var("CARGO_PKG_VERSION").unwrap();
+        let stem = format!("{}_{}", local_crate, crate_version);
+        // If local_crate's mir has already been dumped, whether overwrite it or not
+        let overwrite = env::var("RUSTC_MIR_OVERWRITE").is_ok();
+        if !overwrite && dump_dir.join(format!("{}.json", stem)).exists() {
+            println!("Find {} in dump dir, skip.", local_crate);
+            return true;
+        }
+        println!("Try dump mir for {}", stem);
+        self.target = Some((dump_dir, stem));
+        true
+    }
+
+    // One digraph per body, in the order of the dump.
+    fn dump_dot(&self, file_path: &PathBuf) {
+        let dot = self.dump.bodies.iter().map(body_to_dot).collect::<String>();
+        fs::write(file_path, dot).expect(&format!("Failed to write file {:?}.", file_path));
+    }
+
+    // The manifest lists the bodies that are valid for the latest run of the
+    // crate, so files left behind by removed functions can be told apart.
+    fn dump_manifest(&mut self, file_path: &PathBuf) {
         let mut file = OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .open(file_path)
             .expect(&format!("Failed to create file {:?}.", file_path));
-        file.write_all(serde_json::to_string(&self.all_mirs).unwrap().as_bytes())
+        file.write_all(serde_json::to_string(&self.dump.items).unwrap().as_bytes())
             .unwrap();
-        self.all_mirs.clear();
+    }
+
+    // Remove per-item dump files in `item_dir` which are not in the manifest.
+    // Files are named after the `MirDefId::key` of their item, see
+    // `file_stem_of_key`; other files, which the dumper did not write, are left
+    // alone. The files of bodies which were skipped or failed this time stay, as
+    // do their promoteds'.
+    fn remove_stale_dumps(&mut self, item_dir: &PathBuf) {
+        let Ok(entries) = fs::read_dir(item_dir) else {
+            return;
+        };
+        let valid =
+            self.dump.items.iter().map(|(_, key, _)| key.as_str()).collect::<FxHashSet<_>>();
+        let kept = &self.dump.kept_items;
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().map_or(true, |ext| ext != "json") || !path.is_file() {
+                continue;
+            }
+            let key = path.file_stem().and_then(|stem| stem.to_str()).and_then(key_of_file_stem);
+            let stale = match key {
+                Some(key) => {
+                    let item = key.split_once("::promoted").map_or(key.as_str(), |(item, _)| item);
+                    !valid.contains(key.as_str()) && !kept.iter().any(|kept| kept == item)
+                }
+                None => false,
+            };
+            if stale && fs::remove_file(&path).is_err() {
+                eprintln!("Failed to remove stale dump {:?}.", path);
+            }
+        }
     }
 }
 
 impl Callbacks for SigGenCallback {
+    fn config(&mut self, _config: &mut interface::Config) {
+        self.config = ExtractConfig::from_env();
+    }
+
+    fn after_expansion<'tcx>(
+        &mut self,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        // Built and promoted MIR are stolen by the optimization pipeline during
+        // analysis, so they have to be recorded before that.
+        if env::var("RUSTC_MIR_GEN").is_err() || self.config.phase == ExtractPhase::Optimized {
+            return Compilation::Continue;
+        }
+
+        let Ok(gcx) = queries.global_ctxt() else {
+            return Compilation::Stop;
+        };
+        gcx.peek_mut().enter(|tcx| {
+            if !self.prepare_dump(tcx) {
+                return Compilation::Stop;
+            }
+            if self.target.is_some() {
+                self.dump = extract_crate(tcx, &self.config);
+            }
+            Compilation::Continue
+        })
+    }
+
     fn after_analysis<'tcx>(
         &mut self,
         _compiler: &interface::Compiler,
@@ -1400,57 +1527,60 @@ fn after_analysis<'tcx>(
             return Compilation::Continue;
         }
 
-        // Prepare dump dir.
-        let dump_dir = PathBuf::from(env::var("RUSTC_MIR_DUMP_DIR").unwrap_or("".to_string()));
-        if !dump_dir.is_absolute() {
-            println!("Please set RUSTC_MIR_DUMP_DIR with an absolute path.");
-            println!("Compilation stoped.");
+        let Ok(gcx) = queries.global_ctxt() else {
             return Compilation::Stop;
-        }
-        if !dump_dir.exists() && fs::create_dir_all(&dump_dir).is_err() {
-            println!("Failed to create dir {:?}.", dump_dir);
-        }
-        // Crates to dump
-        let dump_crates = env::var("RUSTC_MIR_DUMP_CRATES")
-                        .unwrap_or("".to_string())
-                        .split(":")
-                        .map(String::from)
-                        .collect::<Vec<_>>();
-        // If local_crate's mir has already been dumped, whether overwrite it or not
-        let overwrite = env::var("RUSTC_MIR_OVERWRITE").is_ok();
-
-        queries.global_ctxt().unwrap().peek_mut().enter(|tcx| {
-            // Match crate
-            let local_crate_num = CrateNum::from_u32(0);
-            let local_crate = tcx.crate_name(local_crate_num).to_ident_string();
-            if !dump_crates.iter().any(|x| *x == local_crate) {
-                return Compilation::Continue;
+        };
+        gcx.peek_mut().enter(|tcx| {
+            // Built and promoted MIR have been extracted in `after_expansion` already.
+            if self.config.phase == ExtractPhase::Optimized {
+                if !self.prepare_dump(tcx) {
+                    return Compilation::Stop;
+                }
+                if self.target.is_some() {
+                    self.dump = extract_crate(tcx, &self.config);
+                }
             }
-            let crate_version = env::var("CARGO_PKG_VERSION").unwrap();
-            let mut dump_file_path = dump_dir.clone();
-            dump_file_path.push(format!("{}_{}.json", local_crate, crate_version));
-            if !overwrite && dump_file_path.exists() {
-                println!("Find {} in dump dir, skip.", local_crate);
+            let Some((dump_dir, stem)) = self.target.take() else {
                 return Compilation::Continue;
+            };
+            // Graphviz CFGs for debugging
+            if env::var("RUSTC_MIR_DUMP_DOT").is_ok() {
+                let mut dot_path = dump_dir.clone();
+                dot_path.push(format!("{}.dot", stem));
+                self.dump_dot(&dot_path);
             }
-
-            println!("Try dump mir for {}_{}", local_crate, crate_version);
-            // Fetch mir functions
-            // It should be all mir but not all function defination? Not sure.
-            // Reference code: pretty.rs::write_mir_pretty
-            let def_id_vec: Vec<DefId> = tcx.mir_keys(()).iter().map(|def_id| def_id.to_def_id()).collect();
-            for def_id in def_id_vec {
-                let instance_mir = tcx.instance_mir(ty::InstanceDef::Item(ty::WithOptConstParam::unknown(def_id)));
-                self.record_mir(instance_mir, def_id, tcx);
+            let mut item_dir = dump_dir.clone();
+            item_dir.push(&stem);
+            // With RUSTC_MIR_DUMP_SPLIT=function the bodies go to one file each
+            // and the crate file only keeps what is shared by them.
+            if env::var("RUSTC_MIR_DUMP_SPLIT").as_deref() == Ok("function") {
+                self.dump_items(&item_dir);
+                // With RUSTC_MIR_DUMP_ONLY the manifest lists a single body.
+                if self.config.only.is_none() {
+                    self.remove_stale_dumps(&item_dir);
+                }
             }
+            let mut dump_file_path = dump_dir.clone();
+            dump_file_path.push(format!("{}.json", stem));
             self.dump_mir(&dump_file_path);
+            let mut manifest_path = dump_dir;
+            manifest_path.push(format!("{}.manifest.json", stem));
+            self.dump_manifest(&manifest_path);
+            if let Some(timings) = &self.config.timings {
+                timings.print();
+            }
             Compilation::Continue
-        });
-        Compilation::Continue
+        })
     }
 }
 
 pub fn mir_gen() -> ! {
+    // Print the schema of the dump instead of compiling, to regenerate bindings.
+    #[cfg(feature = "schemars")]
+    if env::var("RUSTC_MIR_PRINT_SCHEMA").is_ok() {
+        println!("{}", mir_dump::json_schema());
+        process::exit(EXIT_SUCCESS);
+    }
     init_rustc_env_logger();
     signal_handler::install();
     let mut callbacks = SigGenCallback::default();
@@ -0,0 +1,1458 @@
+use super::types::{
+    MirAllocation, MirAssertMessage, MirDefId, MirFloatConst, MirFnAttrs, MirGeneratorInfo, MirLocal, MirPlace,
+    MirProjectionElem, MirRvalue, MirScope, MirSourceFile, MirSpan, MirStatement, MirSwitchArm,
+    MirTerminator, MirUpvar,
+};
+
+use rustc_ast::LitKind;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir as hir;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_index::vec::IndexVec;
+use rustc_middle::{
+    middle::codegen_fn_attrs::CodegenFnAttrFlags,
+    mir::{
+        interpret::{
+            read_target_uint, AllocId, AllocRange, Allocation, ConstAllocation, ConstValue,
+            GlobalAlloc, Pointer, Scalar,
+        },
+        coverage::CodeRegion,
+        terminator::TerminatorKind,
+        visit::Visitor,
+        AssertKind, BasicBlock, BasicBlockData, BinOp, Body, BorrowKind, CastKind, Constant,
+        ConstantKind, Local, Location, Operand, Place, ProjectionElem, Promoted, Rvalue,
+        StatementKind, VarDebugInfoContents,
+    },
+    ty::{
+        self,
+        layout::{fn_can_unwind, IntegerExt},
+        subst::{GenericArgKind, InternalSubsts},
+        Ty, TyCtxt, TypeFoldable,
+    },
+};
+use rustc_span::{sym, ExpnKind, Span};
+use rustc_target::abi::{Integer, Size};
+use rustc_target::spec::abi::Abi;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{self, AssertUnwindSafe};
+
+// Statements need the body for the types of their operands, so unlike
+// terminators they are not converted with `From`.
+pub fn convert_statement<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, k: &StatementKind<'tcx>) -> MirStatement {
+    let expr = match k {
+        StatementKind::Assign(b) => {
+            return MirStatement::Assign {
+                place: format!("{:?}", b.0),
+                rvalue: convert_rvalue(tyctxt, body, &b.1),
+                place_path: convert_place(&b.0),
+                operand_paths: rvalue_places(&b.1).iter().map(convert_place).collect(),
+            };
+        }
+        StatementKind::FakeRead(b) => {
+            return MirStatement::FakeRead {
+                cause: format!("{:?}", b.0),
+                place: format!("{:?}", b.1),
+            };
+        }
+        StatementKind::Deinit(place) => {
+            return MirStatement::Deinit { place: format!("{:?}", place) };
+        }
+        StatementKind::Coverage(c) => {
+            return MirStatement::Coverage {
+                counter: format!("{:?}", c.kind),
+                region: c.code_region.as_ref().map(convert_code_region),
+            };
+        }
+        StatementKind::StorageLive(l) => format!("sl {:?}", l),
+        StatementKind::StorageDead(l) => format!("sd {:?}", l),
+        StatementKind::SetDiscriminant{place, variant_index, ..} => format!("set {:?} {:?}", place, variant_index),
+        _ => format!("{:?}", k),
+    };
+    MirStatement::Other(expr)
+}
+
+fn convert_rvalue<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, rv: &Rvalue<'tcx>) -> MirRvalue {
+    match rv {
+        Rvalue::Cast(kind, opr, to_ty) => {
+            let from_ty = opr.ty(&body.local_decls, tyctxt);
+            MirRvalue::Cast {
+                kind: cast_kind_name(kind, from_ty, *to_ty),
+                operand: format!("{:?}", opr),
+                from_ty: format!("{:?}", from_ty),
+                to_ty: format!("{:?}", to_ty),
+            }
+        }
+        Rvalue::Ref(_, kind, place) => MirRvalue::Ref {
+            borrow_kind: match kind {
+                BorrowKind::Shared => "Shared",
+                BorrowKind::Shallow => "Shallow",
+                BorrowKind::Unique => "Unique",
+                BorrowKind::Mut { .. } => "Mut",
+            }
+            .to_string(),
+            place: format!("{:?}", place),
+        },
+        Rvalue::Len(place) => MirRvalue::Len { place: format!("{:?}", place) },
+        Rvalue::NullaryOp(op, ty) => MirRvalue::NullaryOp {
+            op: format!("{:?}", op),
+            ty: format!("{:?}", ty),
+        },
+        Rvalue::ShallowInitBox(opr, ty) => MirRvalue::ShallowInitBox {
+            operand: format!("{:?}", opr),
+            ty: format!("{:?}", ty),
+        },
+        _ => MirRvalue::Other(format!("{:?}", rv)),
+    }
+}
+
+pub fn convert_place<'tcx>(place: &Place<'tcx>) -> MirPlace {
+    let projection = place
+        .projection
+        .iter()
+        .map(|elem| match elem {
+            ProjectionElem::Deref => MirProjectionElem::Deref,
+            ProjectionElem::Field(field, _) => MirProjectionElem::Field(field.as_u32()),
+            ProjectionElem::Index(local) => MirProjectionElem::Index(local.as_u32()),
+            ProjectionElem::ConstantIndex { offset, min_length, from_end } => {
+                MirProjectionElem::ConstantIndex { offset, min_length, from_end }
+            }
+            ProjectionElem::Subslice { from, to, from_end } => {
+                MirProjectionElem::Subslice { from, to, from_end }
+            }
+            ProjectionElem::Downcast(_, variant) => MirProjectionElem::Downcast(variant.as_u32()),
+        })
+        .collect();
+    MirPlace { local: place.local.as_u32(), projection }
+}
+
+// Places read (through `Copy`/`Move` operands) or borrowed by an rvalue.
+fn rvalue_places<'tcx>(rv: &Rvalue<'tcx>) -> Vec<Place<'tcx>> {
+    match rv {
+        Rvalue::Use(opr)
+        | Rvalue::Repeat(opr, _)
+        | Rvalue::Cast(_, opr, _)
+        | Rvalue::UnaryOp(_, opr)
+        | Rvalue::ShallowInitBox(opr, _) => opr.place().into_iter().collect(),
+        Rvalue::BinaryOp(_, ops) | Rvalue::CheckedBinaryOp(_, ops) => {
+            [&ops.0, &ops.1].into_iter().filter_map(|opr| opr.place()).collect()
+        }
+        Rvalue::Aggregate(_, oprs) => oprs.iter().filter_map(|opr| opr.place()).collect(),
+        Rvalue::Ref(_, _, place)
+        | Rvalue::AddressOf(_, place)
+        | Rvalue::Len(place)
+        | Rvalue::Discriminant(place) => vec![*place],
+        Rvalue::ThreadLocalRef(_) | Rvalue::NullaryOp(..) => vec![],
+    }
+}
+
+// This rustc lumps all numeric `as` casts into `CastKind::Misc`. Split them
+// up by operand types like newer rustc does, as that is what tells a
+// truncating `x as u8` apart from e.g. an enum to integer cast.
+fn cast_kind_name<'tcx>(kind: &CastKind, from_ty: Ty<'tcx>, to_ty: Ty<'tcx>) -> String {
+    if let CastKind::Misc = kind {
+        let numeric = |ty: Ty<'tcx>| {
+            if ty.is_integral() {
+                Some("Int")
+            } else if ty.is_floating_point() {
+                Some("Float")
+            } else {
+                None
+            }
+        };
+        if let (Some(from), Some(to)) = (numeric(from_ty), numeric(to_ty)) {
+            return format!("{}To{}", from, to);
+        }
+    }
+    format!("{:?}", kind)
+}
+
+impl<'tcx> From<&rustc_middle::mir::terminator::TerminatorKind<'tcx>> for MirTerminator {
+    fn from(k: &rustc_middle::mir::terminator::TerminatorKind<'tcx>) -> Self {
+        use rustc_middle::mir::terminator::TerminatorKind;
+        match k {
+            TerminatorKind::Goto { target } => Self::Goto {
+                target: target.as_u32(),
+            },
+            TerminatorKind::SwitchInt { switch_ty, targets, .. } => Self::SwitchInt {
+                switch_ty: format!("{:?}", switch_ty),
+                targets: targets.all_targets().iter().map(|x| x.as_u32()).collect(),
+                arms: targets
+                    .iter()
+                    .map(|(value, target)| MirSwitchArm {
+                        value: Some(value),
+                        rendered: None,
+                        target: target.as_u32(),
+                        variant: None,
+                    })
+                    .chain(std::iter::once(MirSwitchArm {
+                        value: None,
+                        rendered: None,
+                        target: targets.otherwise().as_u32(),
+                        variant: None,
+                    }))
+                    .collect(),
+            },
+            TerminatorKind::Resume => Self::Resume,
+            TerminatorKind::Abort => Self::Abort,
+            TerminatorKind::Return => Self::Return,
+            TerminatorKind::Unreachable => Self::Unreachable,
+            TerminatorKind::Drop { target, unwind, .. } => Self::Drop {
+                target: target.as_u32(),
+                unwind: unwind.map(|x| x.as_u32()),
+            },
+            TerminatorKind::DropAndReplace { target, unwind, .. } => Self::DropAndReplace {
+                target: target.as_u32(),
+                unwind: unwind.map(|x| x.as_u32()),
+            },
+            TerminatorKind::Call {
+                func,
+                args,
+                target,
+                cleanup,
+                ..
+            } => {
+                let func = format!("{:?}", func);
+                let args = args.iter().map(|x| format!("{:?}", x)).collect();
+                let dest = target.map(|x| x.as_u32());
+                let cleanup = cleanup.map(|x| x.as_u32());
+
+                Self::Call {
+                    func,
+                    args,
+                    dest,
+                    cleanup,
+                    can_unwind: cleanup.is_some(),
+                    callee: None,
+                    substs: Vec::new(),
+                    legacy_const_args: Vec::new(),
+                    fn_span: None,
+                }
+            }
+            TerminatorKind::Assert {
+                cond, msg, target, cleanup, ..
+            } => Self::Assert {
+                cond: format!("{:?}", cond),
+                msg: match msg {
+                    AssertKind::BoundsCheck { len, index } => MirAssertMessage::BoundsCheck {
+                        len: format!("{:?}", len),
+                        index: format!("{:?}", index),
+                        len_value: None,
+                        index_value: None,
+                    },
+                    _ => MirAssertMessage::Other(format!("{:?}", msg)),
+                },
+                target: target.as_u32(),
+                cleanup: cleanup.map(|x| x.as_u32()),
+                always_passes: None,
+            },
+            TerminatorKind::Yield { value, resume, drop, .. } => Self::Yield {
+                val: format!("{:?}", value), 
+                resume: resume.as_u32(),
+                drop: drop.map(|x| x.as_u32()),
+            },
+            TerminatorKind::GeneratorDrop => Self::GeneratorDrop,
+            TerminatorKind::FalseEdge {
+                real_target,
+                imaginary_target,
+            } => Self::FalseEdge {
+                real_target: real_target.as_u32(),
+                imaginary_target: imaginary_target.as_u32(),
+            },
+            TerminatorKind::FalseUnwind {
+                real_target,
+                unwind,
+            } => Self::FalseUnwind {
+                real_target: real_target.as_u32(),
+                unwind: unwind.map(|x| x.as_u32()),
+            },
+            TerminatorKind::InlineAsm { destination, .. } => Self::InlineAsm {
+                dest: destination.map(|x| x.as_u32()),
+            },
+            // `MirTerminator::TailCall` has no counterpart in the MIR of this rustc.
+        }
+    }
+}
+
+// Convert a terminator. With `inline_strs`, string constant arguments of calls
+// are rendered as the quoted string instead of the debug form of the operand.
+pub fn convert_terminator<'tcx>(tyctxt: TyCtxt<'tcx>, k: &TerminatorKind<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>, inline_strs: bool) -> MirTerminator {
+    let mut term = MirTerminator::from(k);
+    if inline_strs {
+        if let (MirTerminator::Call { args, .. }, TerminatorKind::Call { args: oprs, .. }) = (&mut term, k) {
+            for (arg, opr) in args.iter_mut().zip(oprs) {
+                if let Some(s) = str_const_from_operand(tyctxt, opr, prom) {
+                    *arg = format!("{:?}", s);
+                }
+            }
+        }
+    }
+    // A cleanup edge may only be there because of drops around the call,
+    // so also ask whether the callee itself can unwind.
+    if let (MirTerminator::Call { can_unwind, callee, substs, legacy_const_args, .. }, TerminatorKind::Call { func, .. }) = (&mut term, k) {
+        if let Some((def_id, fn_substs)) = func.const_fn_def() {
+            if !fn_can_unwind(tyctxt, Some(def_id), tyctxt.fn_sig(def_id).abi()) {
+                *can_unwind = false;
+            }
+            *callee = Some(convert_def_id(tyctxt, def_id));
+            // Regions are erased in MIR, only types and consts are kept.
+            *substs = fn_substs
+                .iter()
+                .filter(|arg| !matches!(arg.unpack(), GenericArgKind::Lifetime(_)))
+                .map(|arg| format!("{:?}", arg))
+                .collect();
+            *legacy_const_args = get_legacy_const_args(tyctxt, def_id, func.constant().map(|c| c.span));
+        }
+    }
+    if let (MirTerminator::Call { fn_span, .. }, TerminatorKind::Call { fn_span: span, .. }) = (&mut term, k) {
+        *fn_span = Some(convert_span(tyctxt, *span));
+    }
+    if let (MirTerminator::SwitchInt { arms, .. }, TerminatorKind::SwitchInt { switch_ty, .. }) = (&mut term, k) {
+        for arm in arms {
+            arm.rendered = arm.value.and_then(|value| render_switch_value(tyctxt, *switch_ty, value));
+        }
+    }
+    // Constant values of bounds checks need `tyctxt` to be evaluated.
+    if let (
+        MirTerminator::Assert { msg: MirAssertMessage::BoundsCheck { len_value, index_value, .. }, .. },
+        TerminatorKind::Assert { msg: AssertKind::BoundsCheck { len, index }, .. },
+    ) = (&mut term, k)
+    {
+        *len_value = const_usize(tyctxt, len);
+        *index_value = const_usize(tyctxt, index);
+    }
+    // The assert passes when the overflow flag of the checked operation equals `expected`.
+    if let (
+        MirTerminator::Assert { always_passes, .. },
+        TerminatorKind::Assert { expected, msg: AssertKind::Overflow(op, l, r), .. },
+    ) = (&mut term, k)
+    {
+        *always_passes = const_overflows(tyctxt, *op, l, r).map(|overflows| overflows == *expected);
+    }
+    term
+}
+
+// Whether `l op r` overflows, for checked operations on constant integers.
+// `None` if an operand is not a constant.
+fn const_overflows<'tcx>(tyctxt: TyCtxt<'tcx>, op: BinOp, l: &Operand<'tcx>, r: &Operand<'tcx>) -> Option<bool> {
+    let param_env = ty::ParamEnv::reveal_all();
+    let eval = |opr: &Operand<'tcx>| {
+        let literal = opr.constant()?.literal;
+        let ty = literal.ty();
+        if !ty.is_integral() || !can_eval_const(tyctxt, literal) {
+            return None;
+        }
+        let size = tyctxt.layout_of(param_env.and(ty)).ok()?.size;
+        Some((size, ty.is_signed(), literal.try_eval_bits(tyctxt, param_env, ty)?))
+    };
+    let (size, signed, l) = eval(l)?;
+    let (r_size, r_signed, r) = eval(r)?;
+    match op {
+        // Shifts overflow when the amount is not below the bit width. A `u128`
+        // amount too large for `i128` wraps to a negative value, also an overflow.
+        BinOp::Shl | BinOp::Shr => {
+            let r = if r_signed { r_size.sign_extend(r) as i128 } else { r as i128 };
+            Some(r < 0 || r >= size.bits() as i128)
+        }
+        BinOp::Add | BinOp::Sub | BinOp::Mul if signed => {
+            let (l, r) = (size.sign_extend(l) as i128, size.sign_extend(r) as i128);
+            let result = match op {
+                BinOp::Add => l.checked_add(r),
+                BinOp::Sub => l.checked_sub(r),
+                _ => l.checked_mul(r),
+            };
+            Some(result.map_or(true, |v| v < size.signed_int_min() || v > size.signed_int_max()))
+        }
+        BinOp::Add | BinOp::Sub | BinOp::Mul => {
+            let result = match op {
+                BinOp::Add => l.checked_add(r),
+                BinOp::Sub => l.checked_sub(r),
+                _ => l.checked_mul(r),
+            };
+            Some(result.map_or(true, |v| v > size.unsigned_int_max()))
+        }
+        _ => None,
+    }
+}
+
+// See `MirTerminator::Call::legacy_const_args`. As in name resolution, only
+// calls to functions of other crates are rewritten, and only when the callee,
+// at `func_span`, is a plain path without generic arguments on its last segment.
+// MIR has no HIR call to check, so this is told from the source of the callee.
+fn get_legacy_const_args(tyctxt: TyCtxt<'_>, def_id: DefId, func_span: Option<Span>) -> Vec<u32> {
+    if def_id.is_local() {
+        return Vec::new();
+    }
+    let snippet = func_span.and_then(|span| tyctxt.sess.source_map().span_to_snippet(span).ok());
+    if let Some(snippet) = snippet {
+        // `f::<3>(a)`, `<T>::f(a)` and `(f)(a)` are left as written.
+        let is_plain_path = !snippet.starts_with('<')
+            && snippet.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        if !is_plain_path {
+            return Vec::new();
+        }
+    }
+    let Some(attr) = tyctxt.get_attr(def_id, sym::rustc_legacy_const_generics) else {
+        return Vec::new();
+    };
+    attr.meta_item_list()
+        .into_iter()
+        .flatten()
+        .filter_map(|meta| match meta.literal()?.kind {
+            LitKind::Int(idx, _) => Some(idx as u32),
+            _ => None,
+        })
+        .collect()
+}
+
+// See `MirSwitchArm::rendered`.
+fn render_switch_value<'tcx>(tyctxt: TyCtxt<'tcx>, switch_ty: Ty<'tcx>, value: u128) -> Option<String> {
+    match switch_ty.kind() {
+        ty::Bool => Some((value != 0).to_string()),
+        ty::Char => char::from_u32(value as u32).map(|c| format!("{:?}", c)),
+        ty::Int(int_ty) => {
+            let size = Integer::from_int_ty(&tyctxt, *int_ty).size();
+            Some((size.sign_extend(value) as i128).to_string())
+        }
+        ty::Uint(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+// Names the variants of the arms of a `SwitchInt` on the discriminant of an
+// enum. The discriminant is read into a local by a statement of the same block.
+pub fn name_switch_arms<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, bb: &BasicBlockData<'tcx>, term: &mut MirTerminator) {
+    let MirTerminator::SwitchInt { arms, .. } = term else {
+        return;
+    };
+    let Some(TerminatorKind::SwitchInt { discr, .. }) = bb.terminator.as_ref().map(|t| &t.kind) else {
+        return;
+    };
+    let Some(discr_local) = discr.place().and_then(|place| place.as_local()) else {
+        return;
+    };
+    let enum_place = bb.statements.iter().rev().find_map(|stmt| match &stmt.kind {
+        StatementKind::Assign(b) if b.0.as_local() == Some(discr_local) => match &b.1 {
+            Rvalue::Discriminant(place) => Some(*place),
+            _ => None,
+        },
+        _ => None,
+    });
+    let Some(enum_place) = enum_place else {
+        return;
+    };
+    let ty::Adt(adt, _) = enum_place.ty(body, tyctxt).ty.kind() else {
+        return;
+    };
+    if !adt.is_enum() {
+        return;
+    }
+    let variants = adt.discriminants(tyctxt).collect::<Vec<_>>();
+    for arm in arms {
+        arm.variant = match arm.value {
+            Some(value) => variants
+                .iter()
+                .find(|(_, discr)| discr.val == value)
+                .map(|(idx, _)| adt.variant(*idx).name.to_string()),
+            None => Some("_".to_string()),
+        };
+    }
+}
+
+fn const_usize<'tcx>(tyctxt: TyCtxt<'tcx>, opr: &Operand<'tcx>) -> Option<u64> {
+    let literal = opr.constant()?.literal;
+    if !can_eval_const(tyctxt, literal) {
+        return None;
+    }
+    literal.try_eval_usize(tyctxt, ty::ParamEnv::reveal_all())
+}
+
+/// Reads an unsigned integer of `size` bytes at `offset` in `alloc`.
+///
+/// Multi-byte values are decoded in the byte order of the target (`tcx.data_layout`),
+/// which is not necessarily the one of the host running the extraction, e.g. when
+/// cross compiling to a big-endian target. Gives `None` for uninitialized bytes
+/// or bytes that are part of a pointer.
+pub fn read_const_uint<'tcx>(tyctxt: TyCtxt<'tcx>, alloc: &Allocation, offset: Size, size: Size) -> Option<u128> {
+    let bytes = alloc.get_bytes(&tyctxt, AllocRange { start: offset, size }).ok()?;
+    read_target_uint(tyctxt.data_layout.endian, bytes).ok()
+}
+
+// Decodes the bytes of a string constant. Bytes which are not valid UTF-8, which
+// only unsafe code or byte strings can give, are decoded lossily; the constant is
+// then also reported in `MirBasicBlock::invalid_utf8_strs`.
+fn decode_str(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// The strings in the constant `val` of type `ty`, joined.
+fn const_str<'tcx>(tyctxt: TyCtxt<'tcx>, val: ConstValue<'tcx>, ty: Ty<'tcx>) -> Option<String> {
+    let strs = const_str_bytes(tyctxt, val, ty);
+    if strs.is_empty() {
+        return None;
+    }
+    Some(strs.into_iter().map(decode_str).collect::<Vec<_>>().join(""))
+}
+
+// Whether `literal` may be evaluated. Evaluating a local named constant builds
+// its CTFE MIR, which steals the MIR of the earlier phases of it, so that is
+// only done once it has happened anyway, as for every constant after analysis.
+fn can_eval_const<'tcx>(tyctxt: TyCtxt<'tcx>, literal: ConstantKind<'tcx>) -> bool {
+    let ConstantKind::Ty(cst) = literal else {
+        return true;
+    };
+    let ty::ConstKind::Unevaluated(uneval) = cst.val() else {
+        return true;
+    };
+    match uneval.def.as_local() {
+        // Associated consts of traits without a default have no MIR of their own.
+        Some(def) if uneval.promoted.is_none() => {
+            tyctxt.is_mir_available(def.did.to_def_id())
+                && tyctxt.mir_built(def).is_stolen()
+                && tyctxt.mir_promoted(def).0.is_stolen()
+        }
+        _ => true,
+    }
+}
+
+// The value of the named constant `cst`, which MIR refers to unevaluated, if
+// it may be evaluated, see `can_eval_const`. `None` for promoteds, see
+// `str_const_from_operand_at`.
+fn eval_named_const<'tcx>(tyctxt: TyCtxt<'tcx>, cst: ty::Const<'tcx>) -> Option<ConstValue<'tcx>> {
+    let ty::ConstKind::Unevaluated(uneval) = cst.val() else {
+        return None;
+    };
+    if uneval.promoted.is_some() || !can_eval_const(tyctxt, ConstantKind::Ty(cst)) {
+        return None;
+    }
+    cst.val().try_eval(tyctxt, ty::ParamEnv::reveal_all())?.ok()
+}
+
+// The bytes of the strings in the constant `val` of type `ty`, not necessarily
+// valid UTF-8, see `decode_str`.
+fn const_str_bytes<'tcx>(tyctxt: TyCtxt<'tcx>, val: ConstValue<'tcx>, ty: Ty<'tcx>) -> Vec<&'tcx [u8]> {
+    // String literals, like
+    // ~~~
+    // let a = "Some string.";
+    // ~~~
+    if let ConstValue::Slice { data, start, end } = val {
+        if matches!(ty.kind(), ty::Ref(_, inner, _) if inner.is_str()) {
+            let range = AllocRange { start: Size::from_bytes(start), size: Size::from_bytes(end - start) };
+            return data.0.get_bytes(&tyctxt, range).ok().into_iter().collect();
+        }
+    }
+    // Newtypes of strings, like
+    // ~~~
+    // #[repr(transparent)]
+    // struct Name(&'static str);
+    // const N: Name = Name("x");
+    // ~~~
+    if let ConstValue::ByRef { alloc, offset } = val {
+        if is_transparent_str(tyctxt, ty) {
+            let range = AllocRange { start: offset, size: tyctxt.data_layout.pointer_size };
+            let relocations = alloc.inner().get_relocations(&tyctxt, range);
+            if let Some(&(ptr_offset, alloc_id)) = relocations.first() {
+                return str_bytes_from_ptr(tyctxt, alloc.inner(), ptr_offset, alloc_id).into_iter().collect();
+            }
+        }
+    }
+    // Thin pointers to string data, like byte string literals
+    // ~~~
+    // let b = b"bytes";
+    // ~~~
+    if let ConstValue::Scalar(Scalar::Ptr(ptr, _)) = val {
+        if let Some(bytes) = str_bytes_from_thin_ptr(tyctxt, ptr, ty) {
+            return vec![bytes];
+        }
+    }
+    // Aggregates with string fields, like
+    // ~~~
+    // const C: Foo = Foo { s: "x" };
+    // ~~~
+    if let ConstValue::ByRef { alloc, offset } = val {
+        return str_bytes_from_byref(tyctxt, alloc.inner(), offset, ty);
+    }
+    vec![]
+}
+
+// Strings referenced from an aggregate constant of type `ty` stored at `offset`
+// in `alloc`. A `&str` field is a pointer into the allocation of the string
+// followed by the length, so every pointer in the value followed by a length
+// that stays within the pointee is taken as a string.
+fn str_bytes_from_byref<'tcx>(tyctxt: TyCtxt<'tcx>, alloc: &Allocation, offset: Size, ty: Ty<'tcx>) -> Vec<&'tcx [u8]> {
+    if !matches!(ty.kind(), ty::Adt(..) | ty::Tuple(..) | ty::Array(..)) {
+        return vec![];
+    }
+    let Ok(layout) = tyctxt.layout_of(ty::ParamEnv::reveal_all().and(ty)) else {
+        return vec![];
+    };
+    let range = AllocRange { start: offset, size: layout.size };
+    alloc
+        .get_relocations(&tyctxt, range)
+        .iter()
+        .filter_map(|&(ptr_offset, alloc_id)| str_bytes_from_ptr(tyctxt, alloc, ptr_offset, alloc_id))
+        .collect()
+}
+
+// The bytes of the string pointed to by the `&str` stored at `ptr_offset` in
+// `alloc`, whose pointer has the relocation `alloc_id`.
+fn str_bytes_from_ptr<'tcx>(tyctxt: TyCtxt<'tcx>, alloc: &Allocation, ptr_offset: Size, alloc_id: AllocId) -> Option<&'tcx [u8]> {
+    let ptr_size = tyctxt.data_layout.pointer_size;
+    // The pointer bytes hold the offset into the pointee allocation.
+    let start = ptr_offset.bytes_usize();
+    let ptr_bytes = alloc.inspect_with_uninit_and_ptr_outside_interpreter(start..start + ptr_size.bytes_usize());
+    let str_offset = read_target_uint(tyctxt.data_layout.endian, ptr_bytes).ok()?;
+    let len = read_const_uint(tyctxt, alloc, ptr_offset + ptr_size, ptr_size)?;
+    let GlobalAlloc::Memory(str_alloc) = tyctxt.global_alloc(alloc_id) else {
+        return None;
+    };
+    str_alloc
+        .inner()
+        .get_bytes(
+            &tyctxt,
+            AllocRange {
+                start: Size::from_bytes(str_offset),
+                size: Size::from_bytes(len),
+            },
+        )
+        .ok()
+}
+
+// The bytes the thin pointer `ptr` of type `ty` points to: the ones of a
+// `[u8; N]`, or the ones of the string of a `&str` it points to.
+fn str_bytes_from_thin_ptr<'tcx>(tyctxt: TyCtxt<'tcx>, ptr: Pointer, ty: Ty<'tcx>) -> Option<&'tcx [u8]> {
+    let pointee = match ty.kind() {
+        ty::Ref(_, pointee, _) | ty::RawPtr(ty::TypeAndMut { ty: pointee, .. }) => *pointee,
+        _ => return None,
+    };
+    let (alloc_id, offset) = ptr.into_parts();
+    let GlobalAlloc::Memory(alloc) = tyctxt.global_alloc(alloc_id) else {
+        return None;
+    };
+    let alloc = alloc.inner();
+    match pointee.kind() {
+        ty::Array(elem, len) if *elem == tyctxt.types.u8 => {
+            let len = len.try_eval_usize(tyctxt, ty::ParamEnv::reveal_all())?;
+            let range = AllocRange { start: offset, size: Size::from_bytes(len) };
+            alloc.get_bytes(&tyctxt, range).ok()
+        }
+        ty::Ref(_, inner, _) if inner.is_str() => {
+            let range = AllocRange { start: offset, size: tyctxt.data_layout.pointer_size };
+            let &(ptr_offset, str_alloc_id) = alloc.get_relocations(&tyctxt, range).first()?;
+            str_bytes_from_ptr(tyctxt, alloc, ptr_offset, str_alloc_id)
+        }
+        _ => None,
+    }
+}
+
+// Whether `ty` is `&str` wrapped in (possibly nested) `#[repr(transparent)]`
+// structs with a single field, which have the layout of the `&str` itself.
+fn is_transparent_str<'tcx>(tyctxt: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    match ty.kind() {
+        ty::Ref(_, inner, _) => inner.is_str(),
+        ty::Adt(def, substs) if def.is_struct() && def.repr().transparent() => {
+            match &def.non_enum_variant().fields[..] {
+                [field] => is_transparent_str(tyctxt, field.ty(tyctxt, substs)),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// `prom` holds the promoted bodies of the body `opr` belongs to. It may be `None`
+// (or empty) when they are not available, e.g. because the promoted query was not
+// run for the body. Strings living in promoteds then simply yield `None`.
+fn str_const_from_operand<'tcx>(tyctxt: TyCtxt<'tcx>, opr: &Operand<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Option<String> {
+    str_const_from_operand_at(tyctxt, opr, prom, 0)
+}
+
+// How many promoted bodies deep `str_const_from_operand_at` follows before giving up. Promoteds
+// only nest a few levels in practice, the bound guards against a cycle through `prom`.
+const MAX_PROMOTED_DEPTH: usize = 64;
+
+// `str_const_from_operand`, with the same contract on `prom`. `depth` counts the
+// promoted bodies entered so far.
+fn str_const_from_operand_at<'tcx>(tyctxt: TyCtxt<'tcx>, opr: &Operand<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>, depth: usize) -> Option<String> {
+    match opr {
+        Operand::Constant(c) => match c.literal {
+            // String literals and other constants holding strings, see
+            // `const_str_bytes`.
+            ConstantKind::Val(val, ty) => const_str(tyctxt, val, ty),
+
+            ConstantKind::Ty(cst) => {
+                // Formatted strings, like
+                // ~~~
+                // let a = format!("{} Test {} String", 4, 5);
+                // ~~~
+                // whose pieces are in a promoted. An operand gives at most one
+                // string, so the strings of all blocks of the promoted are joined.
+                if let ty::ConstKind::Unevaluated(uneval) = cst.val() {
+                    if let Some(promoted) = uneval.promoted {
+                        if let Some(promoted_body) = prom.and_then(|prom| prom.get(promoted)) {
+                            if depth >= MAX_PROMOTED_DEPTH {
+                                // Keep what the outer bodies produced, drop the rest.
+                                debug!(
+                                    "mir_dump: promoted nesting deeper than {} in {:?}",
+                                    MAX_PROMOTED_DEPTH,
+                                    promoted_body.source.def_id()
+                                );
+                                return None;
+                            }
+                            let str_vec = promoted_body
+                                .basic_blocks()
+                                .iter()
+                                .map(|bb| bb_refed_strs(tyctxt, &bb, prom, depth + 1).join(""))
+                                .collect::<Vec<_>>();
+                            if str_vec.len() > 0 {
+                                return Some(str_vec.join(""));
+                            }
+                        }
+                    }
+                }
+                // Named constants, like
+                // ~~~
+                // const C: Foo = Foo { s: "x" };
+                // let c = C;
+                // ~~~
+                let val = eval_named_const(tyctxt, cst)?;
+                const_str(tyctxt, val, cst.ty())
+            }
+        }
+        _ => None,
+    }
+}
+
+// Strings referenced by the statements of `bb`, see `get_bb_refed_strs`. See
+// `str_const_from_operand` for the contract on `prom`.
+pub fn get_bb_stmt_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    bb_stmt_strs(tyctxt, bb, prom, 0)
+}
+
+fn bb_stmt_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>, depth: usize) -> Vec<String> {
+    bb.statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::Assign(b) => match &b.1 {
+                Rvalue::Use(opr) => str_const_from_operand_at(tyctxt, &opr, prom, depth),
+                Rvalue::Repeat(opr, _) => str_const_from_operand_at(tyctxt, opr, prom, depth),
+                Rvalue::Cast(_, opr, _) => str_const_from_operand_at(tyctxt, opr, prom, depth),
+                Rvalue::BinaryOp(_, ops) => str_const_from_operand_at(tyctxt, &ops.0, prom, depth),
+                Rvalue::Aggregate(_, v) => {
+                    let str_vec = v.iter().filter_map(|opr| str_const_from_operand_at(tyctxt, opr, prom, depth)).collect::<Vec<_>>();
+                    if str_vec.len() > 0 {
+                        Some(str_vec.join(""))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+// Strings passed as constant arguments to the `Call` terminating `bb`, see `get_bb_refed_strs`.
+pub fn get_bb_const_arg_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    bb_const_arg_strs(tyctxt, bb, prom, 0)
+}
+
+fn bb_const_arg_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>, depth: usize) -> Vec<String> {
+    // A block has no terminator yet when MIR building stopped at an error.
+    if let Some(rustc_middle::mir::terminator::TerminatorKind::Call{args, ..}) = bb.terminator.as_ref().map(|t| &t.kind) {
+        return args.iter().filter_map(|opr| str_const_from_operand_at(tyctxt, opr, prom, depth)).collect();
+    }
+    Vec::new()
+}
+
+// All strings referenced by `bb`: those of its statements followed by those passed to its call.
+pub fn get_bb_refed_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    bb_refed_strs(tyctxt, bb, prom, 0)
+}
+
+fn bb_refed_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>, depth: usize) -> Vec<String> {
+    let mut ref_strs = bb_stmt_strs(tyctxt, bb, prom, depth);
+    ref_strs.append(&mut bb_const_arg_strs(tyctxt, bb, prom, depth));
+    ref_strs
+}
+
+// (argument index, string) of the `Call` arguments of `bb` which are copies or
+// moves of a local assigned a string constant earlier in the block. A local
+// copied from such a local carries the string too; any other assignment clears it.
+// Flows across blocks are not followed.
+pub fn get_bb_call_arg_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<(u32, String)> {
+    let Some(TerminatorKind::Call { args, .. }) = bb.terminator.as_ref().map(|t| &t.kind) else {
+        return Vec::new();
+    };
+    let mut local_strs: FxHashMap<Local, String> = FxHashMap::default();
+    for stmt in &bb.statements {
+        let StatementKind::Assign(b) = &stmt.kind else {
+            continue;
+        };
+        let (place, rvalue) = &**b;
+        let Some(local) = place.as_local() else {
+            // A write through a projection leaves the base local unchanged as a whole.
+            continue;
+        };
+        let reaching = match rvalue {
+            Rvalue::Use(opr) => str_const_from_operand(tyctxt, opr, prom).or_else(|| {
+                let src = opr.place()?.as_local()?;
+                local_strs.get(&src).cloned()
+            }),
+            _ => None,
+        };
+        match reaching {
+            Some(s) => local_strs.insert(local, s),
+            None => local_strs.remove(&local),
+        };
+    }
+    args.iter()
+        .enumerate()
+        .filter_map(|(idx, opr)| {
+            let local = opr.place()?.as_local()?;
+            local_strs.get(&local).map(|s| (idx as u32, s.clone()))
+        })
+        .collect()
+}
+
+// Whether `def_id` is one of the functions `panic!` and friends lower to.
+// Lang items the crate graph does not define are `None` and never match, e.g.
+// `begin_panic`, which lives in `std`, for `#![no_std]` crates.
+fn is_panic_fn<'tcx>(tyctxt: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    let items = tyctxt.lang_items();
+    [
+        items.panic_fn(),
+        items.panic_fmt(),
+        items.const_panic_fmt(),
+        items.panic_display(),
+        items.begin_panic_fn(),
+    ]
+    .contains(&Some(def_id))
+}
+
+// Strings passed to panic functions. `panic("boom")` takes the message directly,
+// while `panic_fmt` takes a `fmt::Arguments` built by an earlier call, in which
+// case the strings referenced by the block making that call are used.
+pub fn get_panic_messages<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    body.basic_blocks()
+        .iter()
+        .filter(|bb| matches!(called_def(bb), Some(def_id) if is_panic_fn(tyctxt, def_id)))
+        .flat_map(|bb| call_messages(tyctxt, body, bb, prom))
+        .collect()
+}
+
+// Whether `def_id` is one of the constructors of `fmt::Arguments` that
+// `format_args!` and the macros built on it call with the literal pieces of
+// their template.
+fn is_format_args_fn(tyctxt: TyCtxt<'_>, def_id: DefId) -> bool {
+    if tyctxt.crate_name(def_id.krate) != sym::core {
+        return false;
+    }
+    let name = tyctxt.item_name(def_id);
+    if name.as_str() != "new_v1" && name.as_str() != "new_v1_formatted" {
+        return false;
+    }
+    let Some(impl_def_id) = tyctxt.impl_of_method(def_id) else {
+        return false;
+    };
+    matches!(tyctxt.type_of(impl_def_id).kind(), ty::Adt(adt, _) if tyctxt.item_name(adt.did()) == sym::Arguments)
+}
+
+// The literal pieces of every `format_args!` template of the body, once each,
+// in the order they are first found. The pieces are read one by one from the
+// promoted array passed to `fmt::Arguments`; if it was not promoted or the
+// promoteds are not given, all pieces of a template come as one string.
+pub fn get_format_pieces<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    let mut seen = FxHashSet::default();
+    let mut pieces = Vec::new();
+    for bb in body.basic_blocks() {
+        if !matches!(called_def(bb), Some(def_id) if is_format_args_fn(tyctxt, def_id)) {
+            continue;
+        }
+        let Some(TerminatorKind::Call { args, .. }) = bb.terminator.as_ref().map(|t| &t.kind) else {
+            continue;
+        };
+        let Some(template) = args.first() else {
+            continue;
+        };
+        let promoted_body = template.constant().and_then(|c| match c.literal {
+            ConstantKind::Ty(cst) => match cst.val() {
+                ty::ConstKind::Unevaluated(uneval) => uneval.promoted.and_then(|p| prom?.get(p)),
+                _ => None,
+            },
+            ConstantKind::Val(..) => None,
+        });
+        let template_pieces = match promoted_body {
+            Some(promoted_body) => promoted_body
+                .basic_blocks()
+                .iter()
+                .flat_map(|bb| &bb.statements)
+                .flat_map(|stmt| match &stmt.kind {
+                    StatementKind::Assign(assign) => match &assign.1 {
+                        Rvalue::Use(opr) => vec![opr],
+                        Rvalue::Aggregate(_, oprs) => oprs.iter().collect(),
+                        _ => vec![],
+                    },
+                    _ => vec![],
+                })
+                .filter_map(|opr| str_const_from_operand(tyctxt, opr, prom))
+                .collect(),
+            None => str_const_from_operand(tyctxt, template, prom).into_iter().collect::<Vec<_>>(),
+        };
+        for piece in template_pieces {
+            if seen.insert(piece.clone()) {
+                pieces.push(piece);
+            }
+        }
+    }
+    pieces
+}
+
+const ASSERT_MACROS: [&str; 6] =
+    ["assert", "assert_eq", "assert_ne", "debug_assert", "debug_assert_eq", "debug_assert_ne"];
+
+// The messages of the panics that `assert!` and the like expand to: the panic
+// calls from one of these macros, and the calls to `assert_failed` of
+// `assert_eq!` and `assert_ne!`, whose message is an `Option<fmt::Arguments>`.
+pub fn get_assertion_messages<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    body.basic_blocks()
+        .iter()
+        .filter(|bb| {
+            let Some(def_id) = called_def(bb) else {
+                return false;
+            };
+            let is_assert_failed = tyctxt.crate_name(def_id.krate) == sym::core
+                && tyctxt.item_name(def_id).as_str() == "assert_failed";
+            (is_panic_fn(tyctxt, def_id) || is_assert_failed)
+                && bb.terminator().source_info.span.macro_backtrace().any(|expn| {
+                    matches!(expn.kind, ExpnKind::Macro(_, name) if ASSERT_MACROS.contains(&name.as_str()))
+                })
+        })
+        .flat_map(|bb| call_messages(tyctxt, body, bb, prom))
+        .collect()
+}
+
+// The function called by the terminator of `bb`, if known statically.
+fn called_def(bb: &BasicBlockData<'_>) -> Option<DefId> {
+    bb.terminator.as_ref().and_then(|t| called_fn_def(&t.kind))
+}
+
+// Strings passed to the call terminating `bb`, see `get_panic_messages`.
+fn call_messages<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    let mut messages = Vec::new();
+    let Some(TerminatorKind::Call { args, .. }) = bb.terminator.as_ref().map(|t| &t.kind) else {
+        return messages;
+    };
+    for arg in args {
+        if let Some(s) = str_const_from_operand(tyctxt, arg, prom) {
+            messages.push(s);
+            continue;
+        }
+        let Some(local) = arg.place().and_then(|place| place.as_local()) else {
+            continue;
+        };
+        if let Some(def_bb) = defining_call(body, unwrap_single_field(body, local)) {
+            let strs = get_bb_refed_strs(tyctxt, def_bb, prom);
+            if !strs.is_empty() {
+                messages.push(strs.join(""));
+            }
+        }
+    }
+    messages
+}
+
+// The block whose call returns into `local`.
+fn defining_call<'a, 'tcx>(body: &'a Body<'tcx>, local: Local) -> Option<&'a BasicBlockData<'tcx>> {
+    body.basic_blocks().iter().find(|bb| match &bb.terminator {
+        Some(t) => match &t.kind {
+            TerminatorKind::Call { destination, .. } => destination.as_local() == Some(local),
+            _ => false,
+        },
+        None => false,
+    })
+}
+
+// The local wrapped by `local` if it is built as a single field aggregate, like
+// `_2 = Option::Some(move _3)`, or `local` itself.
+fn unwrap_single_field<'tcx>(body: &Body<'tcx>, local: Local) -> Local {
+    let inner = body.basic_blocks().iter().flat_map(|bb| &bb.statements).find_map(|stmt| {
+        let StatementKind::Assign(assign) = &stmt.kind else {
+            return None;
+        };
+        match &assign.1 {
+            Rvalue::Aggregate(_, ops) if assign.0.as_local() == Some(local) && ops.len() == 1 => {
+                ops[0].place().and_then(|place| place.as_local())
+            }
+            _ => None,
+        }
+    });
+    inner.unwrap_or(local)
+}
+
+struct FloatConstCollector<'tcx> {
+    tyctxt: TyCtxt<'tcx>,
+    consts: Vec<MirFloatConst>,
+}
+
+impl<'tcx> Visitor<'tcx> for FloatConstCollector<'tcx> {
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, _location: Location) {
+        let ty = constant.literal.ty();
+        let ty::Float(float_ty) = ty.kind() else {
+            return;
+        };
+        if !can_eval_const(self.tyctxt, constant.literal) {
+            return;
+        }
+        // Take the raw bits, casting the value would not preserve NaN payloads.
+        let param_env = ty::ParamEnv::reveal_all();
+        let Some(bits) = constant.literal.try_eval_bits(self.tyctxt, param_env, ty) else {
+            return;
+        };
+        let value = match float_ty {
+            ty::FloatTy::F32 => f32::from_bits(bits as u32).to_string(),
+            ty::FloatTy::F64 => f64::from_bits(bits as u64).to_string(),
+        };
+        let ty = float_ty.name_str().to_string();
+        self.consts.push(MirFloatConst { ty, bits: bits as u64, value });
+    }
+}
+
+pub fn get_bb_float_consts<'tcx>(tyctxt: TyCtxt<'tcx>, bb_idx: BasicBlock, bb: &BasicBlockData<'tcx>) -> Vec<MirFloatConst> {
+    let mut collector = FloatConstCollector { tyctxt, consts: Vec::new() };
+    collector.visit_basic_block_data(bb_idx, bb);
+    collector.consts
+}
+
+struct InvalidUtf8Collector<'a, 'tcx> {
+    tyctxt: TyCtxt<'tcx>,
+    prom: Option<&'a IndexVec<Promoted, Body<'tcx>>>,
+    // Promoted bodies entered so far, bounded like in `str_const_from_operand_at`.
+    depth: usize,
+    strs: Vec<String>,
+}
+
+impl<'tcx> InvalidUtf8Collector<'_, 'tcx> {
+    fn collect(&mut self, val: ConstValue<'tcx>, ty: Ty<'tcx>) {
+        for bytes in const_str_bytes(self.tyctxt, val, ty) {
+            if std::str::from_utf8(bytes).is_err() {
+                self.strs.push(decode_str(bytes));
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for InvalidUtf8Collector<'_, 'tcx> {
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, _location: Location) {
+        match constant.literal {
+            ConstantKind::Val(val, ty) => self.collect(val, ty),
+            ConstantKind::Ty(cst) => {
+                if let Some(val) = eval_named_const(self.tyctxt, cst) {
+                    return self.collect(val, cst.ty());
+                }
+                // The strings of promoteds are taken as the ones of the block too.
+                let ty::ConstKind::Unevaluated(uneval) = cst.val() else {
+                    return;
+                };
+                let prom = self.prom;
+                let Some(body) = uneval.promoted.and_then(|p| prom.and_then(|prom| prom.get(p))) else {
+                    return;
+                };
+                if self.depth < MAX_PROMOTED_DEPTH {
+                    self.depth += 1;
+                    self.visit_body(body);
+                    self.depth -= 1;
+                }
+            }
+        }
+    }
+}
+
+// See `MirBasicBlock::invalid_utf8_strs`, and `str_const_from_operand` for the
+// contract on `prom`.
+pub fn get_bb_invalid_utf8_strs<'tcx>(tyctxt: TyCtxt<'tcx>, bb_idx: BasicBlock, bb: &BasicBlockData<'tcx>, prom: Option<&IndexVec<Promoted, Body<'tcx>>>) -> Vec<String> {
+    let mut collector = InvalidUtf8Collector { tyctxt, prom, depth: 0, strs: Vec::new() };
+    collector.visit_basic_block_data(bb_idx, bb);
+    collector.strs
+}
+
+struct AllocCollector<'a, 'tcx> {
+    tyctxt: TyCtxt<'tcx>,
+    ids: &'a mut FxHashMap<ConstAllocation<'tcx>, AllocId>,
+    found: Vec<AllocId>,
+}
+
+impl<'tcx> Visitor<'tcx> for AllocCollector<'_, 'tcx> {
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, _location: Location) {
+        let tyctxt = self.tyctxt;
+        let alloc_id = match constant.literal.try_to_value() {
+            Some(ConstValue::Scalar(Scalar::Ptr(ptr, _))) => ptr.into_parts().0,
+            Some(ConstValue::Slice { data, .. } | ConstValue::ByRef { alloc: data, .. }) => {
+                *self.ids.entry(data).or_insert_with(|| tyctxt.create_memory_alloc(data))
+            }
+            _ => return,
+        };
+        self.found.push(alloc_id);
+    }
+}
+
+// Adds the allocations referenced by the constants of `body` and its promoteds,
+// and those they point to, to `allocs`, see `CrateDump::allocations`. `ids` keeps
+// the ids given to allocations without one across bodies.
+pub fn collect_allocations<'tcx>(
+    tyctxt: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    promoteds: Option<&IndexVec<Promoted, Body<'tcx>>>,
+    ids: &mut FxHashMap<ConstAllocation<'tcx>, AllocId>,
+    allocs: &mut BTreeMap<u64, MirAllocation>,
+) {
+    let mut collector = AllocCollector { tyctxt, ids, found: Vec::new() };
+    collector.visit_body(body);
+    for promoted in promoteds.into_iter().flatten() {
+        collector.visit_body(promoted);
+    }
+    let mut pending = collector.found;
+    while let Some(alloc_id) = pending.pop() {
+        if allocs.contains_key(&alloc_id.0.get()) {
+            continue;
+        }
+        let GlobalAlloc::Memory(alloc) = tyctxt.global_alloc(alloc_id) else {
+            continue;
+        };
+        let alloc = alloc.inner();
+        let relocations = alloc
+            .relocations()
+            .iter()
+            .map(|&(offset, target)| {
+                pending.push(target);
+                (offset.bytes(), target.0.get())
+            })
+            .collect();
+        let bytes = alloc.inspect_with_uninit_and_ptr_outside_interpreter(0..alloc.len());
+        let id = alloc_id.0.get();
+        allocs.insert(id, MirAllocation { id, bytes: bytes.to_vec(), relocations });
+    }
+}
+
+#[derive(Default)]
+struct OperandCounter {
+    consts: u32,
+    copies: u32,
+    moves: u32,
+}
+
+impl<'tcx> Visitor<'tcx> for OperandCounter {
+    fn visit_operand(&mut self, operand: &Operand<'tcx>, location: Location) {
+        match operand {
+            Operand::Constant(_) => self.consts += 1,
+            Operand::Copy(_) => self.copies += 1,
+            Operand::Move(_) => self.moves += 1,
+        }
+        self.super_operand(operand, location);
+    }
+}
+
+// Numbers of (constant, copy, move) operands in `bb`.
+pub fn count_bb_operands<'tcx>(bb_idx: BasicBlock, bb: &BasicBlockData<'tcx>) -> (u32, u32, u32) {
+    let mut counter = OperandCounter::default();
+    counter.visit_basic_block_data(bb_idx, bb);
+    (counter.consts, counter.copies, counter.moves)
+}
+
+struct TypeCollector<'a, 'tcx> {
+    tyctxt: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    types: BTreeSet<String>,
+}
+
+impl<'tcx> Visitor<'tcx> for TypeCollector<'_, 'tcx> {
+    fn visit_operand(&mut self, operand: &Operand<'tcx>, location: Location) {
+        self.types.insert(format!("{:?}", operand.ty(self.body, self.tyctxt)));
+        self.super_operand(operand, location);
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Cast(_, _, to_ty) = rvalue {
+            self.types.insert(format!("{:?}", to_ty));
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
+
+// Types of the body's locals, operands and cast targets, sorted and deduplicated.
+pub fn get_referenced_types<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>) -> Vec<String> {
+    let mut collector = TypeCollector { tyctxt, body, types: BTreeSet::new() };
+    for decl in body.local_decls.iter() {
+        collector.types.insert(format!("{:?}", decl.ty));
+    }
+    for (bb_idx, bb) in body.basic_blocks().iter_enumerated() {
+        collector.visit_basic_block_data(bb_idx, bb);
+    }
+    collector.types.into_iter().collect()
+}
+
+pub fn convert_span(tyctxt: TyCtxt<'_>, span: Span) -> MirSpan {
+    let is_macro = span.from_expansion();
+    let macro_name = span.macro_backtrace().last().and_then(|expn| match expn.kind {
+        ExpnKind::Macro(_, name) => Some(name.to_string()),
+        _ => None,
+    });
+    let callsite = span.source_callsite();
+    let source_map = tyctxt.sess.source_map();
+    let lo = source_map.lookup_char_pos(callsite.lo());
+    let hi = source_map.lookup_char_pos(callsite.hi());
+    MirSpan {
+        file: lo.file.name.prefer_local().to_string(),
+        lo_line: lo.line as u32,
+        lo_col: lo.col.0 as u32,
+        hi_line: hi.line as u32,
+        hi_col: hi.col.0 as u32,
+        is_macro,
+        macro_name,
+    }
+}
+
+// See `MirBody::generating_macro`. Desugarings, like the one of `async fn`, are
+// not macros.
+pub fn get_generating_macro(tyctxt: TyCtxt<'_>, def_id: DefId) -> Option<String> {
+    let span = tyctxt.def_span(def_id);
+    let macro_names = span.macro_backtrace().filter_map(|expn| match expn.kind {
+        ExpnKind::Macro(_, name) => Some(name.to_string()),
+        _ => None,
+    });
+    macro_names.last()
+}
+
+// The source files named in `names`, in the order of `names`.
+pub fn get_source_files(tyctxt: TyCtxt<'_>, names: &BTreeSet<String>) -> Vec<MirSourceFile> {
+    let files = tyctxt.sess.source_map().files();
+    names
+        .iter()
+        .filter_map(|name| {
+            let file = files.iter().find(|file| file.name.prefer_local().to_string() == *name)?;
+            let hash = file.src_hash.hash_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+            Some(MirSourceFile {
+                file: name.clone(),
+                hash_kind: format!("{:?}", file.src_hash.kind),
+                hash,
+            })
+        })
+        .collect()
+}
+
+// Coverage regions are already resolved to lines and (1-based) columns.
+fn convert_code_region(region: &CodeRegion) -> MirSpan {
+    MirSpan {
+        file: region.file_name.to_string(),
+        lo_line: region.start_line,
+        lo_col: region.start_col.saturating_sub(1),
+        hi_line: region.end_line,
+        hi_col: region.end_col.saturating_sub(1),
+        is_macro: false,
+        macro_name: None,
+    }
+}
+
+// The layout of a generic type depends on the substitution, and computing it
+// may fail (e.g. for types too big for the target), both give no layout.
+// The locals of `body`, with their layout only if `with_layout` is set: the
+// layout of a generator needs its optimized MIR, which steals the MIR of the
+// earlier phases.
+pub fn get_locals<'tcx>(tyctxt: TyCtxt<'tcx>, body: &Body<'tcx>, with_layout: bool) -> Vec<MirLocal> {
+    body.local_decls
+        .iter_enumerated()
+        .map(|(local, decl)| {
+            let layout = if !with_layout || decl.ty.needs_subst() {
+                None
+            } else {
+                tyctxt.layout_of(ty::ParamEnv::reveal_all().and(decl.ty)).ok()
+            };
+            MirLocal {
+                local: local.as_u32(),
+                ty: format!("{:?}", decl.ty),
+                size: layout.map(|layout| layout.size.bytes()),
+                align: layout.map(|layout| layout.align.abi.bytes()),
+            }
+        })
+        .collect()
+}
+
+pub fn get_generator_info(tyctxt: TyCtxt<'_>, def_id: DefId) -> Option<MirGeneratorInfo> {
+    let ty::Generator(_, substs, _) = tyctxt.type_of(def_id).kind() else {
+        return None;
+    };
+    let substs = substs.as_generator();
+    Some(MirGeneratorInfo {
+        resume_ty: format!("{:?}", substs.resume_ty()),
+        yield_ty: format!("{:?}", substs.yield_ty()),
+        upvar_tys: substs.upvar_tys().map(|ty| format!("{:?}", ty)).collect(),
+    })
+}
+
+pub fn get_closure_upvars(tyctxt: TyCtxt<'_>, def_id: DefId) -> Vec<MirUpvar> {
+    let ty::Closure(_, substs) = tyctxt.type_of(def_id).kind() else {
+        return Vec::new();
+    };
+    let Some(local_id) = def_id.as_local() else {
+        return Vec::new();
+    };
+    // The captures are in the same order as the fields of the closure.
+    tyctxt
+        .typeck(local_id)
+        .closure_min_captures_flattened(def_id)
+        .zip(substs.as_closure().upvar_tys())
+        .map(|(capture, ty)| MirUpvar {
+            name: capture.to_string(tyctxt),
+            ty: format!("{:?}", ty),
+            capture_kind: format!("{:?}", capture.info.capture_kind),
+        })
+        .collect()
+}
+
+// See `MirBody::const_params`.
+pub fn get_const_params(tyctxt: TyCtxt<'_>, def_id: DefId) -> Vec<(String, String)> {
+    let generics = tyctxt.generics_of(def_id);
+    InternalSubsts::identity_for_item(tyctxt, def_id)
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, arg)| match arg.unpack() {
+            GenericArgKind::Const(cst) => {
+                Some((generics.param_at(idx, tyctxt).name.to_string(), cst.to_string()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn get_fn_attrs(tyctxt: TyCtxt<'_>, def_id: DefId) -> MirFnAttrs {
+    if !tyctxt.def_kind(def_id).has_codegen_attrs() {
+        return MirFnAttrs::default();
+    }
+    let attrs = tyctxt.codegen_fn_attrs(def_id);
+    MirFnAttrs {
+        is_inline: attrs.requests_inline(),
+        is_no_mangle: attrs.flags.contains(CodegenFnAttrFlags::NO_MANGLE),
+        is_cold: attrs.flags.contains(CodegenFnAttrFlags::COLD),
+        is_export: attrs.contains_extern_indicator(),
+    }
+}
+
+// (ABI, whether unsafe) of the signature of `def_id`, see `MirBody::abi`.
+pub fn get_abi_and_unsafety(tyctxt: TyCtxt<'_>, def_id: DefId) -> (String, bool) {
+    let sig = match tyctxt.type_of(def_id).kind() {
+        ty::FnDef(..) => tyctxt.fn_sig(def_id),
+        ty::Closure(_, substs) => substs.as_closure().sig(),
+        _ => return (Abi::Rust.name().to_string(), false),
+    };
+    (sig.abi().name().to_string(), sig.unsafety() == hir::Unsafety::Unsafe)
+}
+
+// Whether `def_id` is `#[track_caller]`, see `MirBody::arg_count`.
+pub fn is_track_caller(tyctxt: TyCtxt<'_>, def_id: DefId) -> bool {
+    tyctxt.def_kind(def_id).has_codegen_attrs()
+        && tyctxt.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::TRACK_CALLER)
+}
+
+pub fn get_scopes(tyctxt: TyCtxt<'_>, body: &Body<'_>) -> Vec<MirScope> {
+    body.source_scopes
+        .iter()
+        .map(|scope| MirScope {
+            parent: scope.parent_scope.map(|parent| parent.as_u32()),
+            inlined: scope.inlined.map(|(instance, _)| convert_def_id(tyctxt, instance.def_id())),
+        })
+        .collect()
+}
+
+// Map locals to the names of the user variables they hold.
+// Variables captured by a closure live in projections of the closure
+// environment (e.g. `(*_1).0`), so they are attributed to its base local.
+// Variables whose debug info is a constant have no local and are left out.
+// With `args_only`, only the names of the arguments are kept.
+pub fn get_var_names<'tcx>(body: &Body<'tcx>, args_only: bool) -> Vec<(u32, String)> {
+    body.var_debug_info
+        .iter()
+        .filter_map(|info| match &info.value {
+            VarDebugInfoContents::Place(place) => Some((place.local, info.name.to_string())),
+            VarDebugInfoContents::Const(_) => None,
+        })
+        .filter(|(local, _)| !args_only || (1..=body.arg_count).contains(&local.as_usize()))
+        .map(|(local, name)| (local.as_u32(), name))
+        .collect()
+}
+
+pub fn convert_def_id(tyctxt: TyCtxt<'_>, def_id: DefId) -> MirDefId {
+    MirDefId {
+        crate_name: tyctxt.crate_name(def_id.krate).to_string(),
+        def_index: def_id.index.as_u32(),
+        promoted: None,
+    }
+}
+
+// `def_path_str`, or `DefId(krate:index)` if rendering the path panics, which
+// it may do for ids of compiler generated items. The panic is still reported
+// by the panic hook.
+pub fn def_path_string(tyctxt: TyCtxt<'_>, def_id: DefId) -> String {
+    match panic::catch_unwind(AssertUnwindSafe(|| tyctxt.def_path_str(def_id))) {
+        Ok(path) => path,
+        Err(_) => {
+            debug!("mir_dump: no def path for {:?}", def_id);
+            format!("DefId({}:{})", def_id.krate.as_u32(), def_id.index.as_u32())
+        }
+    }
+}
+
+// The impl or trait of an associated item, see `MirBody::container`.
+pub fn get_container(tyctxt: TyCtxt<'_>, def_id: DefId) -> Option<MirDefId> {
+    let container = tyctxt.impl_of_method(def_id).or_else(|| tyctxt.trait_of_item(def_id))?;
+    Some(convert_def_id(tyctxt, container))
+}
+
+// Statics and consts have MIR bodies too: their initializers. Thread locals
+// are told apart from other statics.
+pub fn body_kind_name(tyctxt: TyCtxt<'_>, def_id: DefId) -> &'static str {
+    match tyctxt.def_kind(def_id) {
+        DefKind::Closure => "Closure",
+        DefKind::Generator => "Generator",
+        DefKind::Const | DefKind::AssocConst | DefKind::AnonConst | DefKind::InlineConst => "Const",
+        DefKind::Static(_) if tyctxt.is_thread_local_static(def_id) => "ThreadLocal",
+        DefKind::Static(_) => "Static",
+        DefKind::Ctor(..) => "Ctor",
+        DefKind::AssocFn if tyctxt.trait_of_item(def_id).is_some() => "TraitDefault",
+        _ => "Fn",
+    }
+}
+
+// See `BB_IS_TRIVIAL`.
+pub fn is_trivial_block(bb: &BasicBlockData<'_>) -> bool {
+    let storage_only = bb.statements.iter().all(|stmt| {
+        matches!(stmt.kind, StatementKind::StorageLive(_) | StatementKind::StorageDead(_) | StatementKind::Nop)
+    });
+    storage_only && matches!(bb.terminator.as_ref().map(|t| &t.kind), Some(TerminatorKind::Goto { .. }))
+}
+
+// The function called by a `Call` terminator.
+// Calls through function pointers or closures have no static callee and give `None`.
+pub fn called_fn_def<'tcx>(term: &TerminatorKind<'tcx>) -> Option<DefId> {
+    if let TerminatorKind::Call { func, .. } = term {
+        return func.const_fn_def().map(|(def_id, _)| def_id);
+    }
+    None
+}
+
+// Read the taint source list, one function path per line.
+// Empty lines and lines starting with `#` are ignored.
+pub fn load_taint_sources(file_path: &str) -> FxHashSet<String> {
+    match std::fs::read_to_string(file_path) {
+        Ok(content) => content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to read taint sources from {}: {}", file_path, e);
+            FxHashSet::default()
+        }
+    }
+}
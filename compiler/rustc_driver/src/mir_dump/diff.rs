@@ -0,0 +1,43 @@
+use super::body_content_hash;
+use super::types::{CrateDump, DumpDelta};
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+
+/// Compares two dumps of the same crate, e.g. from two builds, and reports the
+/// bodies that were added, removed or whose content changed.
+///
+/// Bodies are matched by def path rather than by `MirDefId`: def indices are
+/// assigned in definition order, so adding an item renumbers all items after it.
+/// A body is changed when its [`body_content_hash`] differs. The hash leaves out
+/// def indices, so renumbering alone does not mark a body changed.
+pub fn diff_dumps(old: &CrateDump, new: &CrateDump) -> DumpDelta {
+    let old_bodies = old
+        .bodies
+        .iter()
+        .map(|body| (body.def_path.as_str(), body))
+        .collect::<FxHashMap<_, _>>();
+    let new_paths = new.bodies.iter().map(|body| body.def_path.as_str()).collect::<FxHashSet<_>>();
+    let mut delta = DumpDelta::default();
+    for body in &new.bodies {
+        match old_bodies.get(body.def_path.as_str()) {
+            Some(old_body) if body_content_hash(old_body) != body_content_hash(body) => {
+                delta.changed.push(body.def_id.clone())
+            }
+            Some(_) => {}
+            None => delta.added.push(body.def_id.clone()),
+        }
+    }
+    delta.removed = old
+        .bodies
+        .iter()
+        .filter(|body| !new_paths.contains(body.def_path.as_str()))
+        .map(|body| body.def_id.clone())
+        .collect();
+    delta.added.sort();
+    delta.removed.sort();
+    delta.changed.sort();
+    delta
+}
+
+#[cfg(test)]
+mod tests;
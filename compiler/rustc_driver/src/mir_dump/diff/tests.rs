@@ -0,0 +1,38 @@
+use super::diff_dumps;
+use crate::mir_dump::tests::{block, body, call, def_id};
+use crate::mir_dump::types::{CrateDump, MirBody, MirTerminator};
+
+fn dump(bodies: Vec<MirBody>) -> CrateDump {
+    CrateDump { bodies, ..CrateDump::default() }
+}
+
+fn ret(def_index: u32, def_path: &str) -> MirBody {
+    body(def_index, def_path, vec![block(MirTerminator::Return)])
+}
+
+// `krate::foo`, calling `krate::bar` defined at `bar_index`.
+fn foo(def_index: u32, bar_index: u32) -> MirBody {
+    let call_bar = call("bar", Some(def_id(bar_index)), Some(1));
+    body(def_index, "krate::foo", vec![block(call_bar), block(MirTerminator::Return)])
+}
+
+#[test]
+fn renumbering_is_not_a_change() {
+    let old = dump(vec![foo(1, 2), ret(2, "krate::bar")]);
+    // `krate::baz` is inserted before the others and shifts their def indices.
+    let new = dump(vec![ret(1, "krate::baz"), foo(2, 3), ret(3, "krate::bar")]);
+    let delta = diff_dumps(&old, &new);
+    assert_eq!(delta.added, vec![def_id(1)]);
+    assert!(delta.removed.is_empty());
+    assert!(delta.changed.is_empty());
+}
+
+#[test]
+fn changed_and_removed_bodies() {
+    let old = dump(vec![ret(1, "krate::foo"), ret(2, "krate::bar")]);
+    let new = dump(vec![body(1, "krate::foo", vec![block(MirTerminator::Unreachable)])]);
+    let delta = diff_dumps(&old, &new);
+    assert!(delta.added.is_empty());
+    assert_eq!(delta.removed, vec![def_id(2)]);
+    assert_eq!(delta.changed, vec![def_id(1)]);
+}
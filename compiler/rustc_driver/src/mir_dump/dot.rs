@@ -0,0 +1,36 @@
+use super::types::MirBody;
+
+use std::fmt::Write;
+
+/// Renders the control-flow graph of `body` as a Graphviz digraph, named after
+/// the def path of the body, which tells promoteds apart too.
+///
+/// Each block is a node labeled with its terminator kind and statement count.
+/// Edges taken on unwinding are dashed.
+pub fn body_to_dot(body: &MirBody) -> String {
+    let mut dot = String::new();
+    writeln!(dot, "digraph {:?} {{", body.def_path).unwrap();
+    writeln!(dot, "    node [shape=box];").unwrap();
+    for (idx, bb) in &body.blocks {
+        writeln!(
+            dot,
+            "    bb{} [label=\"bb{}: {} ({} stmts)\"];",
+            idx,
+            idx,
+            bb.term().kind_name(),
+            bb.statements().len()
+        )
+        .unwrap();
+    }
+    for (idx, bb) in &body.blocks {
+        for (target, unwind) in bb.term().successors() {
+            let style = if unwind { " [style=dashed]" } else { "" };
+            writeln!(dot, "    bb{} -> bb{}{};", idx, target, style).unwrap();
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,36 @@
+use super::body_to_dot;
+use crate::mir_dump::tests::{block, body, call};
+use crate::mir_dump::types::MirTerminator;
+
+#[test]
+fn blocks_and_edges() {
+    let mut call_bar = call("bar", None, Some(1));
+    if let MirTerminator::Call { cleanup, .. } = &mut call_bar {
+        *cleanup = Some(2);
+    }
+    let body = body(
+        1,
+        "krate::foo",
+        vec![block(call_bar), block(MirTerminator::Return), block(MirTerminator::Resume)],
+    );
+    assert_eq!(
+        body_to_dot(&body),
+        "digraph \"krate::foo\" {\n\
+         \x20   node [shape=box];\n\
+         \x20   bb0 [label=\"bb0: Call (0 stmts)\"];\n\
+         \x20   bb1 [label=\"bb1: Return (0 stmts)\"];\n\
+         \x20   bb2 [label=\"bb2: Resume (0 stmts)\"];\n\
+         \x20   bb0 -> bb1;\n\
+         \x20   bb0 -> bb2 [style=dashed];\n\
+         }\n"
+    );
+}
+
+#[test]
+fn graphs_are_named_after_the_body() {
+    // Bodies of the same type, like a function and its promoted, get distinct names.
+    let foo = body(1, "krate::foo", vec![block(MirTerminator::Return)]);
+    let promoted = body(1, "krate::foo::promoted[0]", vec![block(MirTerminator::Return)]);
+    assert!(body_to_dot(&foo).starts_with("digraph \"krate::foo\" {\n"));
+    assert!(body_to_dot(&promoted).starts_with("digraph \"krate::foo::promoted[0]\" {\n"));
+}
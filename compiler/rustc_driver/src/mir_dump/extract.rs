@@ -0,0 +1,817 @@
+use super::convert::{
+    body_kind_name, called_fn_def, collect_allocations, count_bb_operands, convert_def_id,
+    convert_span, convert_statement, convert_terminator, def_path_string, get_abi_and_unsafety,
+    get_assertion_messages, get_bb_call_arg_strs, get_bb_const_arg_strs, get_bb_float_consts,
+    get_bb_invalid_utf8_strs, get_bb_refed_strs, get_bb_stmt_strs, get_closure_upvars,
+    get_const_params, get_container, get_fn_attrs, get_format_pieces, get_generating_macro,
+    get_generator_info, get_locals, get_panic_messages, get_referenced_types, get_scopes,
+    get_source_files, get_var_names, is_track_caller, is_trivial_block, load_taint_sources,
+    name_switch_arms,
+};
+use super::types::{CrateDump, MirBasicBlock, MirBody, MirStatement, MirTerminator, SCHEMA_VERSION};
+
+use rustc_data_structures::captures::Captures;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::profiling::print_time_passes_entry;
+use rustc_errors::FatalErrorMarker;
+use rustc_hir::def_id::{DefId, LocalDefId, LOCAL_CRATE};
+use rustc_index::vec::IndexVec;
+use rustc_interface::util;
+use rustc_middle::mir::{traversal, Body, Promoted, Statement, StatementKind};
+use rustc_middle::ty::{self, util::is_intrinsic, TyCtxt};
+use rustc_span::{SourceFileHash, SourceFileHashAlgorithm};
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+/// Which MIR is extracted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtractPhase {
+    /// `mir_built`, the MIR as built from THIR, before constants are promoted.
+    /// It is stolen during analysis, so it has to be extracted before that.
+    Built,
+    /// `mir_promoted`, the MIR borrowck runs on, before any optimization.
+    /// It is stolen during analysis, so it has to be extracted before that.
+    Promoted,
+    /// `instance_mir`, the MIR handed to codegen.
+    Optimized,
+}
+
+#[derive(Debug)]
+pub struct ExtractConfig {
+    pub phase: ExtractPhase,
+    /// Paths of functions whose call sites are reported in `MirBody::taint_sources`.
+    pub taint_sources: FxHashSet<String>,
+    /// Render string constant arguments of calls as the string itself.
+    pub inline_call_strs: bool,
+    /// Only the first `max_blocks` blocks of larger bodies are extracted.
+    pub max_blocks: Option<usize>,
+    /// Only the first `max_stmts_per_block` statements of larger blocks are extracted.
+    pub max_stmts_per_block: Option<usize>,
+    /// Only extract the body whose `def_path_str` is exactly this.
+    pub only: Option<String>,
+    /// Replace the contents of strings by their length and SHA-256 hash.
+    pub redact_strings: bool,
+    /// Store the block strings of a crate once, in `CrateDump::strings`, and
+    /// refer to them by index.
+    pub intern_strings: bool,
+    /// Remove the optional edges to blocks whose terminator is `Unreachable`, see
+    /// `MirTerminator::fold_unreachable`.
+    pub fold_unreachable: bool,
+    /// Let edges to trivial blocks (see `MirBasicBlock::is_trivial`) go to the end
+    /// of the chain of trivial blocks instead.
+    pub merge_trivial_gotos: bool,
+    /// Only record the names of arguments in `MirBody::var_names`.
+    pub arg_names_only: bool,
+    /// Leave `Nop` statements out of blocks, see `MirBasicBlock::nop_count`.
+    pub drop_nops: bool,
+    /// Record the bytes of the allocations referenced by constants in
+    /// `CrateDump::allocations`. Ignored with `redact_strings`, as the bytes
+    /// hold the strings.
+    pub raw_allocs: bool,
+    /// Where to accumulate the time spent extracting, if wanted.
+    pub timings: Option<ExtractTimings>,
+}
+
+/// Wall-clock time spent in each part of the extraction, summed over all bodies.
+#[derive(Debug, Default)]
+pub struct ExtractTimings {
+    /// Converting statements and terminators.
+    pub conversion: Cell<Duration>,
+    /// Collecting the strings referenced by blocks.
+    pub strings: Cell<Duration>,
+    /// Resolving callees, for taint sources and intrinsics.
+    pub call_graph: Cell<Duration>,
+    /// Writing the dump, accounted by the caller.
+    pub serialization: Cell<Duration>,
+}
+
+impl ExtractTimings {
+    /// Runs `f` and adds the time it took to `part`, one of the fields of `self`.
+    pub fn time<R>(&self, part: fn(&Self) -> &Cell<Duration>, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        let total = part(self);
+        total.set(total.get() + start.elapsed());
+        result
+    }
+
+    /// Prints the timings in the format of `-Ztime-passes`.
+    pub fn print(&self) {
+        print_time_passes_entry("mir_dump_conversion", self.conversion.get(), None, None);
+        print_time_passes_entry("mir_dump_strings", self.strings.get(), None, None);
+        print_time_passes_entry("mir_dump_call_graph", self.call_graph.get(), None, None);
+        print_time_passes_entry("mir_dump_serialization", self.serialization.get(), None, None);
+    }
+}
+
+impl Default for ExtractConfig {
+    fn default() -> Self {
+        ExtractConfig {
+            phase: ExtractPhase::Optimized,
+            taint_sources: FxHashSet::default(),
+            inline_call_strs: false,
+            max_blocks: None,
+            max_stmts_per_block: None,
+            only: None,
+            redact_strings: false,
+            intern_strings: false,
+            fold_unreachable: false,
+            merge_trivial_gotos: false,
+            arg_names_only: false,
+            drop_nops: false,
+            raw_allocs: false,
+            timings: None,
+        }
+    }
+}
+
+impl ExtractConfig {
+    /// Reads the configuration from the environment:
+    ///
+    /// - `RUSTC_MIR_DUMP_PHASE`: `built`, `promoted` or `optimized` (the default) MIR.
+    /// - `RUSTC_MIR_TAINT_SOURCES`: file of newline separated taint source paths.
+    /// - `RUSTC_MIR_INLINE_STRS`: if set, inline string arguments of calls.
+    /// - `RUSTC_MIR_DUMP_MAX_BLOCKS`: maximum number of blocks extracted per body.
+    /// - `RUSTC_MIR_DUMP_MAX_STMTS_PER_BLOCK`: maximum number of statements extracted per block.
+    /// - `RUSTC_MIR_DUMP_ONLY`: def path of the only body to extract.
+    /// - `RUSTC_MIR_DUMP_REDACT_STRINGS`: if set, redact the contents of strings.
+    /// - `RUSTC_MIR_DUMP_INTERN_STRS`: if set, intern the strings of blocks.
+    /// - `RUSTC_MIR_DUMP_FOLD_UNREACHABLE`: if set, drop edges to unreachable blocks.
+    /// - `RUSTC_MIR_DUMP_MERGE_GOTOS`: if set, skip trivial blocks in edges.
+    /// - `RUSTC_MIR_DUMP_ARG_NAMES_ONLY`: if set, only name the arguments.
+    /// - `RUSTC_MIR_DUMP_DROP_NOPS`: if set, leave out `Nop` statements.
+    /// - `RUSTC_MIR_DUMP_RAW_ALLOCS`: if set, record the bytes of constant allocations.
+    /// - `RUSTC_MIR_DUMP_TIMINGS`: if set, record where extraction time goes.
+    pub fn from_env() -> Self {
+        let phase = match env::var("RUSTC_MIR_DUMP_PHASE").as_deref() {
+            Ok("built") => ExtractPhase::Built,
+            Ok("promoted") => ExtractPhase::Promoted,
+            _ => ExtractPhase::Optimized,
+        };
+        let taint_sources = match env::var("RUSTC_MIR_TAINT_SOURCES") {
+            Ok(path) => load_taint_sources(&path),
+            Err(_) => FxHashSet::default(),
+        };
+        let inline_call_strs = env::var("RUSTC_MIR_INLINE_STRS").is_ok();
+        let max_blocks =
+            env::var("RUSTC_MIR_DUMP_MAX_BLOCKS").ok().and_then(|max| max.parse().ok());
+        let max_stmts_per_block =
+            env::var("RUSTC_MIR_DUMP_MAX_STMTS_PER_BLOCK").ok().and_then(|max| max.parse().ok());
+        let only = env::var("RUSTC_MIR_DUMP_ONLY").ok();
+        let redact_strings = env::var("RUSTC_MIR_DUMP_REDACT_STRINGS").is_ok();
+        let intern_strings = env::var("RUSTC_MIR_DUMP_INTERN_STRS").is_ok();
+        let fold_unreachable = env::var("RUSTC_MIR_DUMP_FOLD_UNREACHABLE").is_ok();
+        let merge_trivial_gotos = env::var("RUSTC_MIR_DUMP_MERGE_GOTOS").is_ok();
+        let arg_names_only = env::var("RUSTC_MIR_DUMP_ARG_NAMES_ONLY").is_ok();
+        let drop_nops = env::var("RUSTC_MIR_DUMP_DROP_NOPS").is_ok();
+        let raw_allocs = env::var("RUSTC_MIR_DUMP_RAW_ALLOCS").is_ok();
+        let timings = env::var("RUSTC_MIR_DUMP_TIMINGS").is_ok().then(ExtractTimings::default);
+        ExtractConfig {
+            phase,
+            taint_sources,
+            inline_call_strs,
+            max_blocks,
+            max_stmts_per_block,
+            only,
+            redact_strings,
+            intern_strings,
+            fold_unreachable,
+            merge_trivial_gotos,
+            arg_names_only,
+            drop_nops,
+            raw_allocs,
+            timings,
+        }
+    }
+}
+
+/// Converts one MIR body. `promoteds` are the promoted bodies belonging to `body`,
+/// strings referenced through promoteds are lost if they are not given.
+pub fn extract_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    promoteds: Option<&IndexVec<Promoted, Body<'tcx>>>,
+    key: DefId,
+    config: &ExtractConfig,
+) -> MirBody {
+    extract_body_in(tcx, body, promoteds, key, config, config.phase)
+}
+
+// `extract_body` for a body in `phase`, which may differ from `config.phase`:
+// it decides whether scopes and layouts are extracted.
+fn extract_body_in<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    promoteds: Option<&IndexVec<Promoted, Body<'tcx>>>,
+    key: DefId,
+    config: &ExtractConfig,
+    phase: ExtractPhase,
+) -> MirBody {
+    let mut taint_sources = Vec::new();
+    let mut intrinsic_calls = Vec::new();
+    let mut call_arg_strs = Vec::new();
+    // The traversal needs every terminator, so on partially built MIR (after an
+    // earlier error) all blocks are taken as reachable.
+    let complete = body.basic_blocks().iter().all(|bb| bb.terminator.is_some());
+    let reachable = complete.then(|| traversal::reachable_as_bitset(body));
+    let limit = config.max_blocks.unwrap_or(usize::MAX);
+    let max_stmts = config.max_stmts_per_block.unwrap_or(usize::MAX);
+    let mut bbs: Vec<(u32, MirBasicBlock)> = body
+        .basic_blocks()
+        .iter_enumerated()
+        .take(limit)
+        .map(|(bb_idx, bb)| {
+            let (stmt_strs, const_arg_strs, invalid_utf8_strs) = timed(config, |t| &t.strings, || {
+                for (arg_idx, s) in get_bb_call_arg_strs(tcx, bb, promoteds) {
+                    call_arg_strs.push((bb_idx.as_u32(), arg_idx, s));
+                }
+                (
+                    get_bb_stmt_strs(tcx, bb, promoteds),
+                    get_bb_const_arg_strs(tcx, bb, promoteds),
+                    get_bb_invalid_utf8_strs(tcx, bb_idx, bb, promoteds),
+                )
+            });
+            let ref_strs = stmt_strs.iter().chain(&const_arg_strs).cloned().collect();
+            let is_cleanup = bb.is_cleanup;
+            let stmt_limit = bb.statements.len().min(max_stmts);
+            let is_nop = |stmt: &&Statement<'_>| matches!(stmt.kind, StatementKind::Nop);
+            let stmts = bb.statements[..stmt_limit]
+                .iter()
+                .filter(|stmt| !(config.drop_nops && is_nop(stmt)))
+                .collect::<Vec<_>>();
+            let term = bb.terminator.as_ref().map(|t| &t.kind);
+            timed(config, |t| &t.call_graph, || {
+                if let Some(def_id) = term.and_then(called_fn_def) {
+                    if config.taint_sources.contains(&def_path_string(tcx, def_id)) {
+                        taint_sources.push(bb_idx.as_u32());
+                    }
+                    if is_intrinsic(tcx, def_id) {
+                        intrinsic_calls.push((bb_idx.as_u32(), tcx.item_name(def_id).to_string()));
+                    }
+                }
+            });
+            let mut block = timed(config, |t| &t.conversion, || {
+                let statements =
+                    stmts.iter().map(|x| convert_statement(tcx, body, &x.kind)).collect();
+                let mut mir_term = match term {
+                    Some(term) => convert_terminator(tcx, term, promoteds, config.inline_call_strs),
+                    None => MirTerminator::Missing,
+                };
+                name_switch_arms(tcx, body, bb, &mut mir_term);
+                let mut block = MirBasicBlock::new(
+                    statements,
+                    mir_term,
+                    is_cleanup,
+                    reachable.as_ref().map_or(true, |reachable| reachable.contains(bb_idx)),
+                    is_trivial_block(bb),
+                    ref_strs,
+                );
+                (block.const_operand_count, block.copy_operand_count, block.move_operand_count) =
+                    count_bb_operands(bb_idx, bb);
+                block.float_consts = get_bb_float_consts(tcx, bb_idx, bb);
+                block.statement_spans =
+                    stmts.iter().map(|x| convert_span(tcx, x.source_info.span)).collect();
+                if phase == ExtractPhase::Optimized {
+                    block.statement_scopes =
+                        stmts.iter().map(|x| x.source_info.scope.as_u32()).collect();
+                }
+                block.statements_truncated = (bb.statements.len() - stmt_limit) as u32;
+                block.nop_count = bb.statements.iter().filter(is_nop).count() as u32;
+                block
+            });
+            block.stmt_strs = stmt_strs;
+            block.call_arg_strs = const_arg_strs;
+            block.invalid_utf8_strs = invalid_utf8_strs;
+            (bb_idx.as_u32(), block)
+        })
+        .collect::<Vec<_>>();
+    if config.fold_unreachable {
+        fold_unreachable(&mut bbs);
+    }
+    if config.merge_trivial_gotos {
+        merge_trivial_gotos(&mut bbs);
+    }
+    // Closures and generators are bodies of their own, linked to the function
+    // that defines them.
+    let parent =
+        tcx.is_closure(key).then(|| convert_def_id(tcx, tcx.typeck_root_def_id(key)));
+    let (abi, is_unsafe) = get_abi_and_unsafety(tcx, key);
+    let generating_macro = get_generating_macro(tcx, key);
+    let mut body = MirBody {
+        def_id: convert_def_id(tcx, key),
+        def_path: def_path_string(tcx, key),
+        body_kind: body_kind_name(tcx, key).to_string(),
+        parent,
+        container: get_container(tcx, key),
+        mir_phase: format!("{:?}", body.phase),
+        ty: format!("{:?}", tcx.type_of(key)),
+        arg_count: body.arg_count as u32,
+        is_track_caller: is_track_caller(tcx, key),
+        abi,
+        is_unsafe,
+        is_macro_generated: generating_macro.is_some(),
+        generating_macro,
+        blocks: bbs,
+        truncated: body.basic_blocks().len() > limit,
+        taint_sources,
+        var_names: get_var_names(body, config.arg_names_only),
+        intrinsic_calls,
+        panic_messages: get_panic_messages(tcx, body, promoteds),
+        assertion_messages: get_assertion_messages(tcx, body, promoteds),
+        referenced_types: get_referenced_types(tcx, body),
+        call_arg_strs,
+        attrs: get_fn_attrs(tcx, key),
+        const_params: get_const_params(tcx, key),
+        generator_info: get_generator_info(tcx, key),
+        closure_upvars: get_closure_upvars(tcx, key),
+        scopes: match phase {
+            ExtractPhase::Optimized => get_scopes(tcx, body),
+            ExtractPhase::Built | ExtractPhase::Promoted => Vec::new(),
+        },
+        locals: get_locals(tcx, body, phase == ExtractPhase::Optimized),
+    };
+    if config.redact_strings {
+        redact_strings(&mut body);
+    }
+    debug_assert_eq!(body.validate(), Ok(()), "invalid block targets in `{}`", body.def_path);
+    body
+}
+
+// Folds the edges to unreachable blocks, in a single pass: a switch that only
+// led to unreachable blocks becomes unreachable, but the edges to it are kept.
+// Blocks cut off by `max_blocks` are taken as reachable.
+fn fold_unreachable(bbs: &mut [(u32, MirBasicBlock)]) {
+    let unreachable = bbs
+        .iter()
+        .filter(|(_, bb)| bb.is_unreachable_terminator())
+        .map(|(idx, _)| *idx)
+        .collect::<FxHashSet<_>>();
+    for (_, bb) in bbs {
+        bb.fold_unreachable(|target| unreachable.contains(&target));
+    }
+}
+
+// Rewrites every edge to a trivial block into an edge to the first non-trivial
+// block reached through its `Goto`s. Trivial blocks stay in the body so block
+// indices are unchanged; the ones no longer targeted can be dropped by the
+// consumer. Chains are not followed into blocks cut off
+// by `max_blocks`, nor around a cycle of trivial blocks.
+fn merge_trivial_gotos(bbs: &mut [(u32, MirBasicBlock)]) {
+    let gotos = bbs
+        .iter()
+        .filter(|(_, bb)| bb.is_trivial())
+        .filter_map(|(idx, bb)| match bb.term() {
+            MirTerminator::Goto { target } => Some((*idx, *target)),
+            _ => None,
+        })
+        .collect::<FxHashMap<_, _>>();
+    let resolve = |mut target: u32| {
+        let start = target;
+        for _ in 0..gotos.len() {
+            match gotos.get(&target) {
+                Some(&next) if next != start => target = next,
+                _ => break,
+            }
+        }
+        target
+    };
+    for (_, bb) in bbs {
+        bb.map_targets(resolve);
+    }
+}
+
+// Runs `f`, accounting its time to `part` when `config` records timings.
+fn timed<R>(
+    config: &ExtractConfig,
+    part: fn(&ExtractTimings) -> &Cell<Duration>,
+    f: impl FnOnce() -> R,
+) -> R {
+    match &config.timings {
+        Some(timings) => timings.time(part, f),
+        None => f(),
+    }
+}
+
+// Replaces every string found in `body` by `<len=N,sha256=H>`, in the string
+// lists as well as where it is quoted in rendered operands, like `const "s"`.
+// Places, types and the like are left alone, even when they read like one of
+// the strings.
+fn redact_strings(body: &mut MirBody) {
+    let mut strs = FxHashSet::default();
+    for (_, bb) in &body.blocks {
+        let block_strs = bb.stmt_strs.iter().chain(&bb.call_arg_strs).chain(&bb.invalid_utf8_strs);
+        strs.extend(block_strs.cloned());
+    }
+    strs.extend(body.call_arg_strs.iter().map(|(_, _, s)| s.clone()));
+    strs.extend(body.panic_messages.iter().chain(&body.assertion_messages).cloned());
+    let redacted = strs
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let placeholder = redacted_str(&s);
+            (s, placeholder)
+        })
+        .collect::<FxHashMap<_, _>>();
+    let redact = |s: &mut String| {
+        if let Some(placeholder) = redacted.get(s) {
+            *s = placeholder.clone();
+        }
+    };
+    let redact_quoted = |text: &mut String| {
+        for (s, placeholder) in &redacted {
+            let quoted = format!("{:?}", s);
+            if text.contains(&quoted) {
+                *text = text.replace(&quoted, &format!("{:?}", placeholder));
+            }
+        }
+    };
+    for (_, bb) in &mut body.blocks {
+        bb.for_each_str_mut(redact);
+        bb.for_each_operand_text_mut(redact_quoted);
+    }
+    body.call_arg_strs.iter_mut().for_each(|(_, _, s)| redact(s));
+    body.panic_messages.iter_mut().for_each(redact);
+    body.assertion_messages.iter_mut().for_each(redact);
+}
+
+// `s`, redacted like by `redact_strings` with `ExtractConfig::redact_strings`.
+fn redact_if(config: &ExtractConfig, s: String) -> String {
+    if config.redact_strings && !s.is_empty() { redacted_str(&s) } else { s }
+}
+
+fn redacted_str(s: &str) -> String {
+    let hash = SourceFileHash::new(SourceFileHashAlgorithm::Sha256, s);
+    let hex = hash.hash_bytes().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    format!("<len={},sha256={}>", s.len(), hex)
+}
+
+/// Converts every MIR body of the local crate, in the phase selected by `config`,
+/// and hands each of them to `f` together with its `DefId` as soon as it is built.
+/// The promoteds of a body follow it, with the `DefId` of the body.
+/// This lets in-process analyses consume the bodies without collecting them.
+///
+/// In the built and promoted phases this must be called before analysis (e.g.
+/// from `Callbacks::after_expansion`), and locals get no layout, as computing the
+/// one of a generator would steal its MIR. Bodies which are already stolen by then, as
+/// happens to consts evaluated during typeck, are skipped. So are bodies whose
+/// extraction panics, e.g. on a query cycle. The skipped bodies are returned.
+pub fn extract_crate_with<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &ExtractConfig,
+    mut f: impl FnMut(DefId, MirBody),
+) -> Vec<DefId> {
+    for_each_body(tcx, config, |key, body, promoteds| {
+        extract_with_promoteds(tcx, body, promoteds, key, config, &mut f)
+    })
+    .into_iter()
+    .map(|(key, _)| key)
+    .collect()
+}
+
+/// The bodies of [`extract_crate_with`] as an iterator, which converts the
+/// bodies of one `DefId` (the body and its promoteds) at a time as it is
+/// advanced, so a consumer can drop each body before the next is built.
+/// Unlike [`extract_crate_with`] it does not report the skipped bodies.
+pub fn bodies_iter<'a, 'tcx: 'a>(
+    tcx: TyCtxt<'tcx>,
+    config: &'a ExtractConfig,
+) -> impl Iterator<Item = MirBody> + Captures<'tcx> + 'a {
+    tcx.mir_keys(()).iter().filter(move |&&def_id| is_selected(tcx, config, def_id)).flat_map(
+        move |&def_id| {
+            let mut bodies = Vec::new();
+            let _ = try_with_body(tcx, config, def_id, &mut |key, body, promoteds| {
+                extract_with_promoteds(tcx, body, promoteds, key, config, &mut |_, body| {
+                    bodies.push(body)
+                })
+            });
+            bodies
+        },
+    )
+}
+
+/// The strings referenced by every MIR body of the local crate, see
+/// [`extract_crate_with`] for the bodies visited. Only the strings are
+/// collected, which is much cheaper than converting the bodies. They are
+/// redacted with `ExtractConfig::redact_strings`.
+pub fn extract_string_inventory<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &ExtractConfig,
+) -> Vec<(DefId, Vec<String>)> {
+    let mut inventory = Vec::new();
+    for_each_body(tcx, config, |key, body, promoteds| {
+        let strs = body
+            .basic_blocks()
+            .iter()
+            .flat_map(|bb| get_bb_refed_strs(tcx, bb, promoteds))
+            .map(|s| redact_if(config, s))
+            .collect();
+        inventory.push((key, strs));
+    });
+    inventory
+}
+
+/// The literal pieces of the `format!`-style templates of every MIR body of the
+/// local crate, see [`extract_crate_with`] for the bodies visited. Each body
+/// lists a piece once, bodies without templates are left out. The pieces are
+/// redacted with `ExtractConfig::redact_strings`.
+pub fn extract_format_templates<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &ExtractConfig,
+) -> Vec<(DefId, Vec<String>)> {
+    let mut templates = Vec::new();
+    for_each_body(tcx, config, |key, body, promoteds| {
+        let pieces = get_format_pieces(tcx, body, promoteds);
+        let pieces = pieces.into_iter().map(|s| redact_if(config, s)).collect::<Vec<_>>();
+        if !pieces.is_empty() {
+            templates.push((key, pieces));
+        }
+    });
+    templates
+}
+
+// Calls `f` with every MIR body of the local crate selected by `config` and its
+// promoteds. Gives the bodies whose MIR is not available, and those whose
+// extraction failed with the reason, see `try_with_body`.
+fn for_each_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &ExtractConfig,
+    mut f: impl FnMut(DefId, &Body<'tcx>, Option<&IndexVec<Promoted, Body<'tcx>>>),
+) -> Vec<(DefId, Option<String>)> {
+    // It should be all mir but not all function defination? Not sure.
+    // Reference code: pretty.rs::write_mir_pretty
+    let mut found_only = false;
+    let mut skipped = Vec::new();
+    for &def_id in tcx.mir_keys(()).iter() {
+        if !is_selected(tcx, config, def_id) {
+            continue;
+        }
+        found_only = true;
+        match try_with_body(tcx, config, def_id, &mut f) {
+            Ok(true) => {}
+            Ok(false) => skipped.push((def_id.to_def_id(), None)),
+            Err(reason) => skipped.push((def_id.to_def_id(), Some(reason))),
+        }
+    }
+    if let Some(only) = &config.only {
+        if !found_only {
+            tcx.sess.warn(&format!("RUSTC_MIR_DUMP_ONLY: no body found for `{}`", only));
+        }
+    }
+    skipped
+}
+
+// Whether `config.only`, if set, selects `def_id`.
+fn is_selected(tcx: TyCtxt<'_>, config: &ExtractConfig, def_id: LocalDefId) -> bool {
+    config.only.as_ref().map_or(true, |only| def_path_string(tcx, def_id.to_def_id()) == *only)
+}
+
+// `with_body`, catching the panics raised during extraction, e.g. on a query
+// cycle or a fatal error, so the remaining bodies can still be extracted. Gives
+// the reason of the panic; whatever `f` was handed before it is kept.
+//
+// The panic hook is silenced meanwhile, so a failed body is reported by a single
+// warning instead of an ICE report. The query that panicked is left poisoned:
+// the bodies extracted later which depend on it fail as well, with a fatal error.
+fn try_with_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &ExtractConfig,
+    def_id: LocalDefId,
+    f: &mut impl FnMut(DefId, &Body<'tcx>, Option<&IndexVec<Promoted, Body<'tcx>>>),
+) -> Result<bool, String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| with_body(tcx, config, def_id, f)));
+    panic::set_hook(hook);
+    result.map_err(|payload| {
+        let reason = if payload.is::<FatalErrorMarker>() {
+            "fatal error".to_string()
+        } else if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+        tcx.sess.warn(&format!("MIR extraction of {:?} failed ({}), skip.", def_id, reason));
+        reason
+    })
+}
+
+// Calls `f` with the body of `def_id` in the phase of `config` and its promoteds.
+// Returns false, without calling `f`, if that MIR is not available.
+fn with_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &ExtractConfig,
+    def_id: LocalDefId,
+    f: &mut impl FnMut(DefId, &Body<'tcx>, Option<&IndexVec<Promoted, Body<'tcx>>>),
+) -> bool {
+    let key = def_id.to_def_id();
+    match config.phase {
+        ExtractPhase::Optimized => {
+            let body =
+                tcx.instance_mir(ty::InstanceDef::Item(ty::WithOptConstParam::unknown(key)));
+            f(key, body, Some(tcx.promoted_mir(key)));
+        }
+        ExtractPhase::Built => {
+            let body = tcx.mir_built(ty::WithOptConstParam::unknown(def_id));
+            if body.is_stolen() {
+                tcx.sess.warn(&format!("Built MIR of {:?} is not available, skip.", key));
+                return false;
+            }
+            f(key, &body.borrow(), None);
+        }
+        ExtractPhase::Promoted => {
+            let (body, promoteds) = tcx.mir_promoted(ty::WithOptConstParam::unknown(def_id));
+            if body.is_stolen() {
+                tcx.sess.warn(&format!("Promoted MIR of {:?} is not available, skip.", key));
+                return false;
+            }
+            if promoteds.is_stolen() {
+                f(key, &body.borrow(), None);
+            } else {
+                f(key, &body.borrow(), Some(&promoteds.borrow()));
+            }
+        }
+    }
+    true
+}
+
+// Hands the body of `key` and then each of its promoteds to `f`.
+fn extract_with_promoteds<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    promoteds: Option<&IndexVec<Promoted, Body<'tcx>>>,
+    key: DefId,
+    config: &ExtractConfig,
+    f: &mut impl FnMut(DefId, MirBody),
+) {
+    f(key, extract_body(tcx, body, promoteds, key, config));
+    for (idx, promoted) in promoteds.into_iter().flat_map(|p| p.iter_enumerated()) {
+        f(key, extract_promoted(tcx, promoted, promoteds, key, idx, config));
+    }
+}
+
+// Promoteds are numbered per body, so a promoted is identified by the `DefId`
+// of its body together with its index, and its def path is the one of the body
+// followed by `::promoted[N]`, as in MIR dumps, e.g. `main::promoted[0]`.
+fn extract_promoted<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    promoted: &Body<'tcx>,
+    promoteds: Option<&IndexVec<Promoted, Body<'tcx>>>,
+    key: DefId,
+    idx: Promoted,
+    config: &ExtractConfig,
+) -> MirBody {
+    let mut body = extract_body(tcx, promoted, promoteds, key, config);
+    body.def_id.promoted = Some(idx.as_u32());
+    body.def_path = format!("{}::promoted[{}]", body.def_path, idx.as_u32());
+    body.body_kind = "Promoted".to_string();
+    body.parent = Some(convert_def_id(tcx, key));
+    body.ty = format!("{:?}", promoted.return_ty());
+    body.generator_info = None;
+    body
+}
+
+/// Converts the MIR of a monomorphic `instance`, e.g. `<Vec<u8>>::push`, as
+/// resolved by `Instance::resolve`, with its substs applied so types render
+/// concretely rather than as generic parameters.
+///
+/// This is a different mode from the other entry points, which extract the
+/// generic body of each `DefId` of the local crate once: an instance may be of
+/// any crate, and a generic function has one instance per distinct substs it is
+/// used with. The optimized MIR is extracted, with its scopes and layouts,
+/// whatever `config.phase` is.
+/// `def_path` and `ty` are those of the instance, while item data such as
+/// `const_params` stays the one of the generic item.
+///
+/// Gives `None` for an item without MIR, like an upstream function which is
+/// neither generic nor `#[inline]`: it is codegened in its own crate, which
+/// does not encode its MIR. Shims are built on demand and always have MIR.
+pub fn extract_instance<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: ty::Instance<'tcx>,
+    config: &ExtractConfig,
+) -> Option<MirBody> {
+    let param_env = ty::ParamEnv::reveal_all();
+    let key = instance.def_id();
+    if matches!(instance.def, ty::InstanceDef::Item(_)) && !tcx.is_mir_available(key) {
+        return None;
+    }
+    let body = instance.subst_mir_and_normalize_erasing_regions(
+        tcx,
+        param_env,
+        tcx.instance_mir(instance.def).clone(),
+    );
+    let promoteds = match instance.def {
+        ty::InstanceDef::Item(def) => Some(instance.subst_mir_and_normalize_erasing_regions(
+            tcx,
+            param_env,
+            tcx.promoted_mir(def.did).clone(),
+        )),
+        _ => None,
+    };
+    let mut mir_body =
+        extract_body_in(tcx, &body, promoteds.as_ref(), key, config, ExtractPhase::Optimized);
+    mir_body.def_path = tcx.def_path_str_with_substs(key, instance.substs);
+    mir_body.ty = format!("{:?}", instance.ty(tcx, param_env));
+    Some(mir_body)
+}
+
+/// Converts every MIR body of the local crate into a [`CrateDump`],
+/// see [`extract_crate_with`].
+pub fn extract_crate<'tcx>(tcx: TyCtxt<'tcx>, config: &ExtractConfig) -> CrateDump {
+    let mut dump = CrateDump {
+        schema_version: SCHEMA_VERSION,
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        // As `rustc_driver::version` prints it.
+        rustc_version: format!("rustc {}", util::version_str().unwrap_or("unknown version")),
+        ..CrateDump::default()
+    };
+    let raw_allocs = config.raw_allocs && !config.redact_strings;
+    let mut alloc_ids = FxHashMap::default();
+    let mut allocs = BTreeMap::new();
+    let skipped = for_each_body(tcx, config, |key, body, promoteds| {
+        if raw_allocs {
+            collect_allocations(tcx, body, promoteds, &mut alloc_ids, &mut allocs);
+        }
+        extract_with_promoteds(tcx, body, promoteds, key, config, &mut |key, body| {
+            let hash = tcx.def_path_hash(key).0.to_hex();
+            let hash = match body.def_id.promoted {
+                Some(idx) => format!("{}_promoted{}", hash, idx),
+                None => hash,
+            };
+            dump.items.push((hash, body.def_id.key(), body.def_path.clone()));
+            dump.bodies.push(body);
+        })
+    });
+    dump.allocations = allocs.into_values().collect();
+    let mut files = BTreeSet::new();
+    for body in &dump.bodies {
+        span_files(body, &mut files);
+    }
+    dump.source_files = get_source_files(tcx, &files);
+    for (key, reason) in skipped {
+        dump.kept_items.push(convert_def_id(tcx, key).key());
+        match reason {
+            Some(reason) => dump.failed.push((def_path_string(tcx, key), reason)),
+            None => dump.skipped.push(def_path_string(tcx, key)),
+        }
+    }
+    dump.string_usage = string_usage(&dump.bodies);
+    if config.intern_strings {
+        intern_strings(&mut dump);
+    }
+    dump
+}
+
+// See `CrateDump::string_usage`.
+fn string_usage(bodies: &[MirBody]) -> Vec<(String, Vec<String>)> {
+    let mut usage = BTreeMap::<_, BTreeSet<_>>::new();
+    for body in bodies {
+        for (_, bb) in &body.blocks {
+            for s in bb.stmt_strs.iter().chain(&bb.call_arg_strs) {
+                usage.entry(s).or_default().insert(body.def_id.key());
+            }
+        }
+    }
+    usage.into_iter().map(|(s, keys)| (s.clone(), keys.into_iter().collect())).collect()
+}
+
+// Moves the strings of all blocks to `dump.strings`, each distinct string once,
+// in the order they are first found.
+fn intern_strings(dump: &mut CrateDump) {
+    let mut ids = FxHashMap::default();
+    let strings = &mut dump.strings;
+    for body in &mut dump.bodies {
+        for (_, bb) in &mut body.blocks {
+            bb.intern_strs(|s| {
+                *ids.entry(s).or_insert_with_key(|s| {
+                    strings.push(s.clone());
+                    strings.len() as u32 - 1
+                })
+            });
+        }
+    }
+}
+
+// Adds the files of all spans in `body` to `files`.
+fn span_files(body: &MirBody, files: &mut BTreeSet<String>) {
+    for (_, bb) in &body.blocks {
+        files.extend(bb.statement_spans.iter().map(|span| span.file.clone()));
+        for stmt in bb.statements() {
+            if let MirStatement::Coverage { region: Some(region), .. } = stmt {
+                files.insert(region.file.clone());
+            }
+        }
+        if let MirTerminator::Call { fn_span: Some(span), .. } = bb.term() {
+            files.insert(span.file.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,128 @@
+use super::{fold_unreachable, merge_trivial_gotos, redact_strings, redacted_str};
+use crate::mir_dump::tests::{block, body, call};
+use crate::mir_dump::types::{
+    MirBasicBlock, MirPlace, MirRvalue, MirStatement, MirSwitchArm, MirTerminator,
+};
+
+fn blocks(blocks: Vec<MirBasicBlock>) -> Vec<(u32, MirBasicBlock)> {
+    body(1, "krate::foo", blocks).blocks
+}
+
+fn switch(targets: Vec<u32>) -> MirTerminator {
+    // One arm per target but the `otherwise` one, which comes last.
+    let arm = |(i, &target): (usize, &u32)| MirSwitchArm {
+        value: Some(i as u128),
+        rendered: None,
+        target,
+        variant: None,
+    };
+    let arms = targets[..targets.len() - 1].iter().enumerate().map(arm).collect();
+    MirTerminator::SwitchInt { switch_ty: "u8".to_string(), targets, arms }
+}
+
+fn targets(bbs: &[(u32, MirBasicBlock)]) -> Vec<Vec<u32>> {
+    bbs.iter().map(|(_, bb)| bb.term().successors().into_iter().map(|(t, _)| t).collect()).collect()
+}
+
+#[test]
+fn fold_unreachable_drops_edges() {
+    let mut bbs = blocks(vec![
+        block(switch(vec![1, 2, 3])),
+        block(MirTerminator::Return),
+        block(MirTerminator::Unreachable),
+        block(call("foo", None, Some(2))),
+    ]);
+    fold_unreachable(&mut bbs);
+    assert_eq!(targets(&bbs), vec![vec![1, 3], vec![], vec![], vec![]]);
+    let MirTerminator::SwitchInt { arms, .. } = bbs[0].1.term() else { panic!() };
+    assert_eq!(arms.iter().map(|arm| arm.target).collect::<Vec<_>>(), vec![1]);
+}
+
+#[test]
+fn fold_unreachable_makes_dead_switches_unreachable() {
+    let mut bbs = blocks(vec![
+        block(MirTerminator::Goto { target: 1 }),
+        block(switch(vec![2, 2])),
+        block(MirTerminator::Unreachable),
+    ]);
+    fold_unreachable(&mut bbs);
+    assert!(bbs[1].1.is_unreachable_terminator());
+    // A single pass: the edge to the switch which became unreachable is kept.
+    assert_eq!(targets(&bbs), vec![vec![1], vec![], vec![]]);
+}
+
+#[test]
+fn fold_unreachable_keeps_otherwise() {
+    let mut bbs = blocks(vec![
+        block(switch(vec![1, 2, 2])),
+        block(MirTerminator::Return),
+        block(MirTerminator::Unreachable),
+    ]);
+    fold_unreachable(&mut bbs);
+    // The value arm to bb2 is gone, the `otherwise` edge to it stays last.
+    assert_eq!(targets(&bbs), vec![vec![1, 2], vec![], vec![]]);
+    let MirTerminator::SwitchInt { arms, .. } = bbs[0].1.term() else { panic!() };
+    assert_eq!(arms.iter().map(|arm| arm.target).collect::<Vec<_>>(), vec![1]);
+}
+
+#[test]
+fn merge_trivial_gotos_skips_chains() {
+    // Not flagged trivial, as if it had other statements than storage markers.
+    let goto = MirTerminator::Goto { target: 4 };
+    let kept = MirBasicBlock::new(Vec::new(), goto, false, true, false, Vec::new());
+    let mut bbs = blocks(vec![
+        block(switch(vec![1, 3])),
+        block(MirTerminator::Goto { target: 2 }),
+        block(MirTerminator::Goto { target: 3 }),
+        kept,
+        block(call("foo", None, Some(1))),
+    ]);
+    merge_trivial_gotos(&mut bbs);
+    assert_eq!(targets(&bbs), vec![vec![3, 3], vec![3], vec![3], vec![4], vec![3]]);
+}
+
+#[test]
+fn merge_trivial_gotos_stops_on_cycles() {
+    let mut bbs = blocks(vec![
+        block(MirTerminator::Goto { target: 1 }),
+        block(MirTerminator::Goto { target: 0 }),
+    ]);
+    merge_trivial_gotos(&mut bbs);
+    assert_eq!(targets(&bbs), vec![vec![0], vec![1]]);
+}
+
+// Strings which read like a place or a type are only redacted where they are
+// strings, not in the places and types of the statements.
+#[test]
+fn redact_strings_leaves_places_and_types() {
+    let assign = |local: u32, rvalue| MirStatement::Assign {
+        place: format!("_{}", local),
+        rvalue,
+        place_path: MirPlace { local, projection: Vec::new() },
+        operand_paths: Vec::new(),
+    };
+    let cast = MirRvalue::Cast {
+        kind: "IntToInt".to_string(),
+        operand: "_3".to_string(),
+        from_ty: "u32".to_string(),
+        to_ty: "u8".to_string(),
+    };
+    let stmts = vec![assign(1, MirRvalue::Other("const \"_1\"".to_string())), assign(2, cast)];
+    let strs = vec!["_1".to_string(), "u8".to_string()];
+    let mut bb = MirBasicBlock::new(stmts, MirTerminator::Return, false, true, false, strs.clone());
+    bb.stmt_strs = strs;
+    let mut body = body(1, "krate::foo", vec![bb]);
+    redact_strings(&mut body);
+
+    let bb = &body.blocks[0].1;
+    assert_eq!(bb.stmt_strs, vec![redacted_str("_1"), redacted_str("u8")]);
+    let [
+        MirStatement::Assign { place, rvalue: MirRvalue::Other(text), .. },
+        MirStatement::Assign { rvalue: MirRvalue::Cast { to_ty, .. }, .. },
+    ] = bb.statements() else {
+        panic!()
+    };
+    assert_eq!(place, "_1");
+    assert_eq!(*text, format!("const {:?}", redacted_str("_1")));
+    assert_eq!(to_ty, "u8");
+}
@@ -0,0 +1,38 @@
+use super::types::{CrateDump, MirTerminator, WorkspaceDump};
+
+use rustc_data_structures::fx::FxHashSet;
+
+/// Merges the dumps of several crates into one [`WorkspaceDump`].
+///
+/// Bodies are identified by their `MirDefId`, which is the same in the dump of
+/// the defining crate and in calls from other crates, so cross-crate calls line
+/// up in the merged call graph. A body found in several dumps (e.g. when a crate
+/// was dumped twice) is kept once and its id is reported in `collisions`.
+pub fn merge_dumps(dumps: Vec<CrateDump>) -> WorkspaceDump {
+    let mut merged = WorkspaceDump::default();
+    let mut seen = FxHashSet::default();
+    for dump in dumps {
+        for body in dump.bodies {
+            if !seen.insert(body.def_id.clone()) {
+                merged.collisions.push(body.def_id.key());
+                continue;
+            }
+            for (_, bb) in &body.blocks {
+                if let MirTerminator::Call { callee: Some(callee), .. } = bb.term() {
+                    merged.call_graph.push((body.def_id.key(), callee.key()));
+                }
+            }
+            merged.def_paths.push((body.def_id.key(), body.def_path.clone()));
+            merged.bodies.push(body);
+        }
+    }
+    merged.call_graph.sort();
+    merged.call_graph.dedup();
+    merged.def_paths.sort();
+    merged.collisions.sort();
+    merged.collisions.dedup();
+    merged
+}
+
+#[cfg(test)]
+mod tests;
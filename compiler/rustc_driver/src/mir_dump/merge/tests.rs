@@ -0,0 +1,42 @@
+use super::merge_dumps;
+use crate::mir_dump::tests::{block, body, call};
+use crate::mir_dump::types::{CrateDump, MirDefId, MirTerminator};
+
+fn std_id(def_index: u32) -> MirDefId {
+    MirDefId { crate_name: "std".to_string(), def_index, promoted: None }
+}
+
+#[test]
+fn merges_call_graphs_across_crates() {
+    let mut callee = body(7, "std::f", vec![block(MirTerminator::Return)]);
+    callee.def_id = std_id(7);
+    let callee_dump = CrateDump { bodies: vec![callee], ..CrateDump::default() };
+    let caller = body(
+        1,
+        "krate::g",
+        vec![block(call("f", Some(std_id(7)), Some(1))), block(call("f", Some(std_id(7)), None))],
+    );
+    let caller_dump = CrateDump { bodies: vec![caller], ..CrateDump::default() };
+    let merged = merge_dumps(vec![caller_dump, callee_dump]);
+    assert_eq!(merged.bodies.len(), 2);
+    assert_eq!(merged.call_graph, vec![("krate:1".to_string(), "std:7".to_string())]);
+    assert_eq!(
+        merged.def_paths,
+        vec![
+            ("krate:1".to_string(), "krate::g".to_string()),
+            ("std:7".to_string(), "std::f".to_string()),
+        ]
+    );
+    assert!(merged.collisions.is_empty());
+}
+
+#[test]
+fn reports_collisions() {
+    let dump = || CrateDump {
+        bodies: vec![body(1, "krate::g", vec![block(MirTerminator::Return)])],
+        ..CrateDump::default()
+    };
+    let merged = merge_dumps(vec![dump(), dump()]);
+    assert_eq!(merged.bodies.len(), 1);
+    assert_eq!(merged.collisions, vec!["krate:1".to_string()]);
+}
@@ -0,0 +1,66 @@
+//! Extraction of MIR bodies into serializable form.
+//!
+//! [`extract_body`] and [`extract_crate`] convert MIR into the types of this module,
+//! which derive `Serialize`/`Deserialize` and carry no `'tcx` lifetime, so tools can
+//! store the result or read it back without a compiler session. The rustc glue used
+//! for the conversion is kept private to this module.
+
+mod convert;
+mod diff;
+mod dot;
+mod extract;
+mod merge;
+mod slice;
+mod text;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use convert::read_const_uint;
+pub use diff::diff_dumps;
+pub use dot::body_to_dot;
+pub use extract::{
+    bodies_iter, extract_body, extract_crate, extract_crate_with, extract_format_templates,
+    extract_instance, extract_string_inventory, ExtractConfig, ExtractPhase, ExtractTimings,
+};
+pub use merge::merge_dumps;
+pub use slice::slice_between;
+pub use text::body_to_text;
+pub use types::*;
+
+use rustc_data_structures::stable_hasher::StableHasher;
+
+use std::hash::Hasher;
+
+/// Hash of the serialized content of `dump`, to tell whether the MIR of a crate
+/// changed between two builds without comparing whole dumps.
+///
+/// The hash only depends on the dump itself (not on file names or timestamps),
+/// but it is only comparable between dumps of the same schema version: any change
+/// to the serialized types changes the hash of otherwise identical MIR.
+pub fn content_hash(dump: &CrateDump) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write(serde_json::to_string(dump).unwrap().as_bytes());
+    hasher.finish()
+}
+
+/// Hash of the serialized content of a single body, see [`content_hash`].
+///
+/// The def indices of the `MirDefId`s in the body, i.e. of the body itself, its
+/// callees, parent, container and inlined bodies (see `MirBody::map_def_ids`),
+/// are left out: adding an item renumbers the items defined after it without
+/// changing their MIR.
+pub fn body_content_hash(body: &MirBody) -> u64 {
+    let mut body = body.clone();
+    body.map_def_ids(|id| id.def_index = 0);
+    let mut hasher = StableHasher::new();
+    hasher.write(serde_json::to_string(&body).unwrap().as_bytes());
+    hasher.finish()
+}
+
+/// JSON Schema of the dump file, i.e. of [`CrateDump`] and all types it contains.
+#[cfg(feature = "schemars")]
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&schemars::schema_for!(CrateDump)).unwrap()
+}
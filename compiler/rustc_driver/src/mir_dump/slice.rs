@@ -0,0 +1,51 @@
+use super::types::MirBody;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+
+/// Cuts `body` down to the blocks on some path from block `from` to block `to`,
+/// i.e. the blocks reachable from `from` which can reach `to`.
+///
+/// Edges leaving the slice are removed as by `MirBasicBlock::retain_targets`, so
+/// every target left in the result is a block of the slice. Per-block tables of
+/// the body, like `taint_sources`, only keep the entries of the blocks of the slice.
+/// The slice is empty if there is no such path.
+pub fn slice_between(mut body: MirBody, from: u32, to: u32) -> MirBody {
+    let mut successors: FxHashMap<u32, Vec<u32>> = FxHashMap::default();
+    let mut predecessors: FxHashMap<u32, Vec<u32>> = FxHashMap::default();
+    for (idx, bb) in &body.blocks {
+        for (target, _) in bb.term().successors() {
+            successors.entry(*idx).or_default().push(target);
+            predecessors.entry(target).or_default().push(*idx);
+        }
+    }
+    let forward = reachable(from, &successors);
+    let backward = reachable(to, &predecessors);
+    let keep = |idx: u32| forward.contains(&idx) && backward.contains(&idx);
+
+    body.blocks.retain(|(idx, _)| keep(*idx));
+    for (_, bb) in &mut body.blocks {
+        bb.retain_targets(keep);
+    }
+    debug_assert!(
+        body.blocks.iter().all(|(_, bb)| bb.term().successors().iter().all(|(t, _)| keep(*t)))
+    );
+    body.taint_sources.retain(|idx| keep(*idx));
+    body.intrinsic_calls.retain(|(idx, _)| keep(*idx));
+    body.call_arg_strs.retain(|(idx, _, _)| keep(*idx));
+    body
+}
+
+// Blocks reachable from `start` through `edges`, including `start` itself.
+fn reachable(start: u32, edges: &FxHashMap<u32, Vec<u32>>) -> FxHashSet<u32> {
+    let mut seen = FxHashSet::default();
+    let mut stack = vec![start];
+    while let Some(idx) = stack.pop() {
+        if seen.insert(idx) {
+            stack.extend(edges.get(&idx).into_iter().flatten());
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests;
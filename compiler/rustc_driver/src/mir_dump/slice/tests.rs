@@ -0,0 +1,43 @@
+use super::slice_between;
+use crate::mir_dump::tests::{block, body};
+use crate::mir_dump::types::{MirBody, MirTerminator};
+
+// `bb0` branches to `bb1` and `bb2`, which lead to the returns `bb3` and `bb4`.
+fn diamond() -> MirBody {
+    let mut body = body(
+        1,
+        "krate::foo",
+        vec![
+            block(MirTerminator::SwitchInt {
+                switch_ty: "bool".to_string(),
+                targets: vec![1, 2],
+                arms: Vec::new(),
+            }),
+            block(MirTerminator::Goto { target: 3 }),
+            block(MirTerminator::Goto { target: 4 }),
+            block(MirTerminator::Return),
+            block(MirTerminator::Return),
+        ],
+    );
+    body.taint_sources = vec![1, 2];
+    body
+}
+
+fn indices(body: &MirBody) -> Vec<u32> {
+    body.blocks.iter().map(|(idx, _)| *idx).collect()
+}
+
+#[test]
+fn keeps_the_blocks_between() {
+    let slice = slice_between(diamond(), 0, 3);
+    assert_eq!(indices(&slice), vec![0, 1, 3]);
+    let MirTerminator::SwitchInt { targets, .. } = slice.blocks[0].1.term() else { panic!() };
+    assert_eq!(targets, &vec![1]);
+    assert_eq!(slice.taint_sources, vec![1]);
+    assert_eq!(slice.validate(), Ok(()));
+}
+
+#[test]
+fn is_empty_without_a_path() {
+    assert!(slice_between(diamond(), 1, 4).blocks.is_empty());
+}
@@ -0,0 +1,61 @@
+//! Bodies built by hand for the tests of the `mir_dump` modules.
+
+use super::types::{MirBasicBlock, MirBody, MirDefId, MirFnAttrs, MirTerminator};
+
+pub(super) fn def_id(def_index: u32) -> MirDefId {
+    MirDefId { crate_name: "krate".to_string(), def_index, promoted: None }
+}
+
+// A reachable block without statements.
+pub(super) fn block(term: MirTerminator) -> MirBasicBlock {
+    let is_trivial = matches!(term, MirTerminator::Goto { .. });
+    MirBasicBlock::new(Vec::new(), term, false, true, is_trivial, Vec::new())
+}
+
+pub(super) fn call(func: &str, callee: Option<MirDefId>, target: Option<u32>) -> MirTerminator {
+    MirTerminator::Call {
+        func: func.to_string(),
+        args: Vec::new(),
+        dest: target,
+        cleanup: None,
+        can_unwind: false,
+        callee,
+        substs: Vec::new(),
+        legacy_const_args: Vec::new(),
+        fn_span: None,
+    }
+}
+
+// A function body with the blocks `bb0`, `bb1`, ... in order.
+pub(super) fn body(def_index: u32, def_path: &str, blocks: Vec<MirBasicBlock>) -> MirBody {
+    MirBody {
+        def_id: def_id(def_index),
+        def_path: def_path.to_string(),
+        body_kind: "Fn".to_string(),
+        parent: None,
+        container: None,
+        mir_phase: "Optimized".to_string(),
+        ty: "fn()".to_string(),
+        arg_count: 0,
+        is_track_caller: false,
+        abi: "Rust".to_string(),
+        is_unsafe: false,
+        is_macro_generated: false,
+        generating_macro: None,
+        truncated: false,
+        taint_sources: Vec::new(),
+        var_names: Vec::new(),
+        intrinsic_calls: Vec::new(),
+        panic_messages: Vec::new(),
+        assertion_messages: Vec::new(),
+        referenced_types: Vec::new(),
+        call_arg_strs: Vec::new(),
+        attrs: MirFnAttrs::default(),
+        const_params: Vec::new(),
+        generator_info: None,
+        closure_upvars: Vec::new(),
+        scopes: Vec::new(),
+        locals: Vec::new(),
+        blocks: blocks.into_iter().enumerate().map(|(i, bb)| (i as u32, bb)).collect(),
+    }
+}
@@ -0,0 +1,139 @@
+use super::types::{MirAssertMessage, MirBody, MirRvalue, MirStatement, MirTerminator};
+
+use std::fmt::Write;
+
+/// Renders `body` as text close to rustc's own MIR dumps (`--emit=mir`).
+///
+/// Only what the dump holds can be shown: places written by calls and the
+/// operands of switches and drops are not part of it and are left out. The
+/// strings referenced by a block are listed in a comment at its end.
+pub fn body_to_text(body: &MirBody) -> String {
+    let mut text = String::new();
+    writeln!(text, "// MIR for `{}` ({})", body.def_path, body.mir_phase).unwrap();
+    writeln!(text, "{}: {} {{", body.def_path, body.ty).unwrap();
+    for (idx, bb) in &body.blocks {
+        let cleanup = if bb.is_cleanup() { " (cleanup)" } else { "" };
+        writeln!(text, "    bb{}{}: {{", idx, cleanup).unwrap();
+        for stmt in bb.statements() {
+            writeln!(text, "        {};", statement_to_text(stmt)).unwrap();
+        }
+        writeln!(text, "        {};", terminator_to_text(bb.term())).unwrap();
+        let strs = bb.stmt_strs.iter().chain(&bb.call_arg_strs);
+        let strs = strs.map(|s| format!("{:?}", s)).collect::<Vec<_>>();
+        if !strs.is_empty() {
+            writeln!(text, "        // ref_strs: {}", strs.join(", ")).unwrap();
+        }
+        writeln!(text, "    }}").unwrap();
+    }
+    if body.truncated {
+        writeln!(text, "    // truncated").unwrap();
+    }
+    writeln!(text, "}}").unwrap();
+    text
+}
+
+fn statement_to_text(stmt: &MirStatement) -> String {
+    match stmt {
+        MirStatement::Assign { place, rvalue, .. } => {
+            format!("{} = {}", place, rvalue_to_text(rvalue))
+        }
+        MirStatement::FakeRead { cause, place } => format!("FakeRead({}, {})", cause, place),
+        MirStatement::Deinit { place } => format!("Deinit({})", place),
+        MirStatement::Coverage { counter, .. } => format!("Coverage::{}", counter),
+        MirStatement::Other(text) => text.clone(),
+    }
+}
+
+fn rvalue_to_text(rvalue: &MirRvalue) -> String {
+    match rvalue {
+        MirRvalue::Cast { kind, operand, to_ty, .. } => {
+            format!("{} as {} ({})", operand, to_ty, kind)
+        }
+        MirRvalue::Ref { borrow_kind, place } => {
+            let prefix = match borrow_kind.as_str() {
+                "Mut" => "&mut ",
+                "Shallow" => "&shallow ",
+                "Unique" => "&uniq ",
+                _ => "&",
+            };
+            format!("{}{}", prefix, place)
+        }
+        MirRvalue::Len { place } => format!("Len({})", place),
+        MirRvalue::CopyForDeref { place } => format!("deref_copy {}", place),
+        MirRvalue::NullaryOp { op, ty } => format!("{}({})", op, ty),
+        MirRvalue::ShallowInitBox { operand, ty } => {
+            format!("ShallowInitBox({}, {})", operand, ty)
+        }
+        MirRvalue::Other(text) => text.clone(),
+    }
+}
+
+// ` -> [name: bbN, ...]` for the targets which are set.
+fn targets(targets: &[(&str, Option<u32>)]) -> String {
+    let targets = targets
+        .iter()
+        .filter_map(|(name, target)| target.map(|target| format!("{}: bb{}", name, target)))
+        .collect::<Vec<_>>();
+    if targets.is_empty() { String::new() } else { format!(" -> [{}]", targets.join(", ")) }
+}
+
+fn terminator_to_text(term: &MirTerminator) -> String {
+    match term {
+        MirTerminator::Goto { target } => format!("goto -> bb{}", target),
+        MirTerminator::SwitchInt { arms, .. } => {
+            let arms = arms
+                .iter()
+                .map(|arm| match (&arm.rendered, arm.value) {
+                    (Some(rendered), _) => format!("{}: bb{}", rendered, arm.target),
+                    (None, Some(value)) => format!("{}: bb{}", value, arm.target),
+                    (None, None) => format!("otherwise: bb{}", arm.target),
+                })
+                .collect::<Vec<_>>();
+            format!("switchInt -> [{}]", arms.join(", "))
+        }
+        MirTerminator::Resume => "resume".to_string(),
+        MirTerminator::Abort => "abort".to_string(),
+        MirTerminator::Return => "return".to_string(),
+        MirTerminator::Unreachable => "unreachable".to_string(),
+        MirTerminator::Drop { target, unwind } => {
+            format!("drop{}", targets(&[("return", Some(*target)), ("unwind", *unwind)]))
+        }
+        MirTerminator::DropAndReplace { target, unwind } => {
+            format!("replace{}", targets(&[("return", Some(*target)), ("unwind", *unwind)]))
+        }
+        MirTerminator::Call { func, args, dest, cleanup, .. } => format!(
+            "{}({}){}",
+            func,
+            args.join(", "),
+            targets(&[("return", *dest), ("unwind", *cleanup)])
+        ),
+        MirTerminator::TailCall { func, args } => format!("become {}({})", func, args.join(", ")),
+        MirTerminator::Assert { cond, msg, target, cleanup, .. } => {
+            let msg = match msg {
+                MirAssertMessage::BoundsCheck { len, index, .. } => {
+                    format!("index out of bounds: the length is {} but the index is {}", len, index)
+                }
+                MirAssertMessage::Other(text) => text.clone(),
+            };
+            format!(
+                "assert({}, {:?}){}",
+                cond,
+                msg,
+                targets(&[("success", Some(*target)), ("unwind", *cleanup)])
+            )
+        }
+        MirTerminator::Yield { val, resume, drop } => {
+            format!("yield({}){}", val, targets(&[("resume", Some(*resume)), ("drop", *drop)]))
+        }
+        MirTerminator::GeneratorDrop => "generator_drop".to_string(),
+        MirTerminator::FalseEdge { real_target, imaginary_target } => format!(
+            "falseEdge{}",
+            targets(&[("real", Some(*real_target)), ("imaginary", Some(*imaginary_target))])
+        ),
+        MirTerminator::FalseUnwind { real_target, unwind } => {
+            format!("falseUnwind{}", targets(&[("real", Some(*real_target)), ("cleanup", *unwind)]))
+        }
+        MirTerminator::InlineAsm { dest } => format!("asm!(..){}", targets(&[("return", *dest)])),
+        MirTerminator::Missing => "// no terminator".to_string(),
+    }
+}
@@ -0,0 +1,1041 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::mem;
+
+// Version of the serialized format, bumped on every incompatible change to
+// the types of this file.
+//
+// Fields serialize in declaration order, and the order is part of the format:
+// within a schema version, the small fields of a body or block (ids, kinds,
+// flags) come before its large arrays (`blocks`, `statements`), so streaming
+// consumers can decide to skip an object before reaching the bulk of it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// All bodies extracted from one crate, with what is needed to tell whether a
+// consumer can read them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CrateDump {
+    // `SCHEMA_VERSION` of the extractor which wrote the dump.
+    pub schema_version: u32,
+    pub crate_name: String,
+    // Version of the compiler, the first line of `rustc --version`, e.g.
+    // `rustc 1.62.0-nightly (e1b71feb5 2022-05-20)`. The commit hash and date
+    // are part of the version rustc was built with, and left out when it was
+    // not built from a git checkout.
+    pub rustc_version: String,
+    // The files spans of the bodies point into, sorted by name.
+    pub source_files: Vec<MirSourceFile>,
+    // Def paths of the bodies whose MIR was not available.
+    pub skipped: Vec<String>,
+    // (def path, reason) of the bodies whose extraction failed, e.g. on a query
+    // cycle. What was extracted before the failure, like the body itself when one
+    // of its promoteds fails, is still in `bodies`.
+    pub failed: Vec<(String, String)>,
+    // Strings of all blocks when they are interned, see `MirBasicBlock::ref_str_ids`.
+    pub strings: Vec<String>,
+    // Each string referenced by blocks, with the `MirDefId::key`s of the bodies
+    // referencing it. Both are sorted.
+    pub string_usage: Vec<(String, Vec<String>)>,
+    // With `RUSTC_MIR_DUMP_RAW_ALLOCS`, the allocations referenced by constants of
+    // the bodies and those they point to, ordered by id.
+    pub allocations: Vec<MirAllocation>,
+    pub bodies: Vec<MirBody>,
+    // (def path hash, `MirDefId::key`, def path) of every body, in the same order
+    // as `bodies`. Split dump files are named after the key, see `file_stem_of_key`.
+    #[serde(skip)]
+    pub items: Vec<(String, String, String)>,
+    // `MirDefId::key`s of the bodies in `skipped` and `failed`, whose files from
+    // an earlier run are kept.
+    #[serde(skip)]
+    pub kept_items: Vec<String>,
+}
+
+// All bodies of a workspace, see `merge_dumps`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WorkspaceDump {
+    pub bodies: Vec<MirBody>,
+    // `MirDefId::key`s of (caller, callee) of every statically known call, sorted.
+    // The callee may be outside of the workspace, e.g. in `std`.
+    pub call_graph: Vec<(String, String)>,
+    // `MirDefId::key` and def path of every body, sorted by key.
+    pub def_paths: Vec<(String, String)>,
+    // Keys of bodies found in more than one dump. Only the first body is kept.
+    pub collisions: Vec<String>,
+}
+
+// Bodies that differ between two dumps of a crate, see `diff_dumps`. Each list
+// is sorted.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DumpDelta {
+    // Ids in the new dump.
+    pub added: Vec<MirDefId>,
+    // Ids in the old dump.
+    pub removed: Vec<MirDefId>,
+    // Ids in the new dump.
+    pub changed: Vec<MirDefId>,
+}
+
+// Identifies an item across crates. A `DefId` only holds the crate number,
+// which is assigned per compilation session, so the crate is named instead.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirDefId {
+    pub crate_name: String,
+    pub def_index: u32,
+    // For promoted bodies, the index of the promoted in the body `def_index`.
+    pub promoted: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirBody {
+    pub def_id: MirDefId,
+    pub def_path: String,
+    // `Fn`, `TraitDefault`, `Closure`, `Generator`, `Const`, `Static`,
+    // `ThreadLocal`, `Ctor` or `Promoted`. Bodies of consts and statics are their
+    // initializers. `TraitDefault` is the default body of a trait method, which
+    // is generic over `Self`: its types and callee substs may mention `Self`.
+    pub body_kind: String,
+    // For closures and generators, the function they are defined in. For
+    // promoteds, the body they were promoted from.
+    pub parent: Option<MirDefId>,
+    // For associated functions and consts, the impl or trait they belong to;
+    // promoteds share the one of their body. Closures have none, see `parent`.
+    pub container: Option<MirDefId>,
+    // The `MirPhase` the body was in when extracted, e.g. `Built`,
+    // `ConstsPromoted` or `Optimized`.
+    pub mir_phase: String,
+    pub ty: String,
+    // Number of arguments, their locals are `_1` to `_{arg_count}`. This never
+    // counts the `&Location` argument that `#[track_caller]` functions receive
+    // from their caller: it is added by the ABI and has no local in MIR.
+    pub arg_count: u32,
+    // `#[track_caller]`, the function takes the implicit location argument
+    // described at `arg_count`.
+    pub is_track_caller: bool,
+    // ABI of the signature, e.g. `Rust`, `C` or `rust-call` for closures. Bodies
+    // without a signature, like consts and generators, have `Rust`.
+    pub abi: String,
+    // Whether the function is `unsafe fn`.
+    pub is_unsafe: bool,
+    // Whether the item was produced by a macro, e.g. the `fmt` of a
+    // `#[derive(Debug)]`, and the name of the outermost such macro (`Debug`).
+    pub is_macro_generated: bool,
+    pub generating_macro: Option<String>,
+    // Whether `blocks` was cut off at `ExtractConfig::max_blocks`.
+    pub truncated: bool,
+    // Indices of blocks whose terminator calls one of the configured taint sources.
+    pub taint_sources: Vec<u32>,
+    // Source names of locals, from the body's `var_debug_info`.
+    // Only the arguments with `ExtractConfig::arg_names_only`.
+    pub var_names: Vec<(u32, String)>,
+    // (block index, intrinsic name) of every block whose terminator calls an intrinsic.
+    pub intrinsic_calls: Vec<(u32, String)>,
+    // Strings passed to `panic!` and the like.
+    pub panic_messages: Vec<String>,
+    // Messages of the panics of failed `assert!`, `assert_eq!` and the like,
+    // including their `debug_` variants. Also in `panic_messages`, except for
+    // the ones of `assert_eq!` and `assert_ne!`.
+    pub assertion_messages: Vec<String>,
+    // Every type the body mentions, sorted.
+    pub referenced_types: Vec<String>,
+    // (call block, argument index, string) of call arguments holding a string
+    // constant assigned earlier in the same block.
+    pub call_arg_strs: Vec<(u32, u32, String)>,
+    pub attrs: MirFnAttrs,
+    // (name, value) of the const generic parameters. Bodies are polymorphic, so
+    // the value is the parameter itself: the values a caller instantiates the
+    // body with are in the `substs` of its `Call`.
+    pub const_params: Vec<(String, String)>,
+    // Set for generator bodies, including the ones of `async` blocks and functions.
+    pub generator_info: Option<MirGeneratorInfo>,
+    // Captured variables of closures, in the order of the closure's fields.
+    pub closure_upvars: Vec<MirUpvar>,
+    // Source scopes of optimized MIR, indexed by `MirBasicBlock::statement_scopes`.
+    // Empty for built and promoted MIR, which have not been inlined into.
+    pub scopes: Vec<MirScope>,
+    // Every local, in order, starting with the return place `_0`.
+    pub locals: Vec<MirLocal>,
+    // Kept last, see `SCHEMA_VERSION`.
+    pub blocks: Vec<(u32, MirBasicBlock)>,
+}
+
+// Codegen attributes of the function. All false for bodies which have none,
+// like consts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirFnAttrs {
+    // `#[inline]` or `#[inline(always)]`
+    pub is_inline: bool,
+    pub is_no_mangle: bool,
+    pub is_cold: bool,
+    // Exported under a fixed symbol name: `#[no_mangle]`, `#[export_name]` or
+    // an external `#[linkage]`.
+    pub is_export: bool,
+}
+
+// The interface of a generator: what `resume` takes and what `yield` gives,
+// and the types of the captured variables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirGeneratorInfo {
+    pub resume_ty: String,
+    pub yield_ty: String,
+    pub upvar_tys: Vec<String>,
+}
+
+// A source scope. The scopes of a function inlined into the body form a subtree
+// whose root has `inlined` set to the inlined function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirScope {
+    pub parent: Option<u32>,
+    pub inlined: Option<MirDefId>,
+}
+
+// A variable captured by a closure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirUpvar {
+    // The captured place, e.g. `x` or `x.0` for a disjoint field capture.
+    pub name: String,
+    // Type of the closure field holding the capture, a reference for `ByRef`.
+    pub ty: String,
+    // `ByValue` or `ByRef(..)` with the borrow kind, e.g. `ByRef(MutBorrow)`.
+    pub capture_kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirLocal {
+    pub local: u32,
+    pub ty: String,
+    // Layout of the type in bytes, unknown for generic types.
+    pub size: Option<u64>,
+    pub align: Option<u64>,
+}
+
+// Bit positions of `MirBasicBlock::flags`.
+pub const BB_IS_CLEANUP: u32 = 1 << 0;
+pub const BB_REACHABLE: u32 = 1 << 1;
+pub const BB_HAS_CALL: u32 = 1 << 2;
+pub const BB_HAS_ASSERT: u32 = 1 << 3;
+pub const BB_HAS_STRING_REF: u32 = 1 << 4;
+// The block returns from the function, with `Return` or a `TailCall`.
+pub const BB_IS_EXIT: u32 = 1 << 5;
+// The block leaves the function while unwinding (`Resume` or `Abort`).
+pub const BB_IS_UNWIND_EXIT: u32 = 1 << 6;
+// The terminator is `Unreachable`.
+pub const BB_IS_UNREACHABLE: u32 = 1 << 7;
+// The block only has `StorageLive`, `StorageDead` and `Nop` statements and
+// ends in a `Goto`.
+pub const BB_IS_TRIVIAL: u32 = 1 << 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirBasicBlock {
+    // Boolean block properties packed as `BB_*` bits, see the accessors below.
+    flags: u32,
+    term: MirTerminator,
+    // `stmt_strs` followed by `call_arg_strs`.
+    ref_strs: Vec<String>,
+    // Strings referenced by the statements.
+    pub stmt_strs: Vec<String>,
+    // Strings passed as constant arguments to the terminating call.
+    pub call_arg_strs: Vec<String>,
+    // With interned strings, the three lists above are empty and these hold the
+    // indices of their strings in `CrateDump::strings` instead.
+    ref_str_ids: Vec<u32>,
+    pub stmt_str_ids: Vec<u32>,
+    pub call_arg_str_ids: Vec<u32>,
+    // Operands of the block's statements and terminator, by kind.
+    pub const_operand_count: u32,
+    pub copy_operand_count: u32,
+    pub move_operand_count: u32,
+    // Number of statements left out of `statements` and `statement_spans` at
+    // `ExtractConfig::max_stmts_per_block`. The other fields cover them all.
+    pub statements_truncated: u32,
+    // Number of `Nop` statements, left where optimizations removed statements.
+    // With `ExtractConfig::drop_nops` they are left out of `statements` and
+    // `statement_spans`, and not part of `statements_truncated` either.
+    pub nop_count: u32,
+    // Float constants used by the block's statements and terminator. In built
+    // and promoted MIR, the named constants of the crate are not evaluated yet
+    // and left out.
+    pub float_consts: Vec<MirFloatConst>,
+    // String constants of the block whose bytes are not valid UTF-8, including
+    // the ones in aggregates, newtypes, byte strings and promoteds of the block.
+    // Only unsafe code can build such a `&str`. They are rendered lossily, here
+    // as well as in `stmt_strs` and `call_arg_strs`.
+    pub invalid_utf8_strs: Vec<String>,
+    // Kept after the fields above, see `SCHEMA_VERSION`.
+    statements: Vec<MirStatement>,
+    // Source span of each statement, in the same order as `statements`.
+    pub statement_spans: Vec<MirSpan>,
+    // Index in `MirBody::scopes` of the source scope of each statement, in the
+    // same order as `statements`. Only set for optimized MIR.
+    pub statement_scopes: Vec<u32>,
+}
+
+// A source file with the hash rustc computed of its content, to check that a
+// checkout matches the source the dump was built from. `MirSpan::file` refers
+// to `file`.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirSourceFile {
+    pub file: String,
+    // `Md5`, `Sha1` or `Sha256`, see `-Zsrc-hash-algorithm`.
+    pub hash_kind: String,
+    // Hex encoded.
+    pub hash: String,
+}
+
+// The memory of a constant. Slices and constants stored by reference have no
+// `AllocId` of their own, they are given one when the dump is built.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirAllocation {
+    pub id: u64,
+    // All bytes, uninitialized ones included. Pointers hold their offset here.
+    pub bytes: Vec<u8>,
+    // (offset, id of the allocation pointed to) of every pointer in `bytes`.
+    // Only memory allocations are in the dump, not functions or statics.
+    pub relocations: Vec<(u64, u64)>,
+}
+
+// A source location. Spans from macro expansions are located at the
+// outermost macro call site, so they always point into user code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirSpan {
+    pub file: String,
+    // 1-based lines, 0-based columns.
+    pub lo_line: u32,
+    pub lo_col: u32,
+    pub hi_line: u32,
+    pub hi_col: u32,
+    pub is_macro: bool,
+    // Name of the outermost macro, e.g. `println` for code expanded from `println!`.
+    pub macro_name: Option<String>,
+}
+
+// A float constant. JSON has no NaN or infinity, so the value is kept as
+// its raw bits and additionally rendered as text (`NaN`, `inf`, `1.5`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirFloatConst {
+    // `f32` or `f64`
+    pub ty: String,
+    pub bits: u64,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MirTerminator {
+    Goto {
+        target: u32,
+    },
+    SwitchInt {
+        // Type of the value switched on.
+        switch_ty: String,
+        // All targets, the `otherwise` target last.
+        targets: Vec<u32>,
+        // The same targets with the values leading to them.
+        arms: Vec<MirSwitchArm>,
+    },
+    Resume,
+    Abort,
+    Return,
+    Unreachable,
+    Drop {
+        target: u32,
+        unwind: Option<u32>,
+    },
+    DropAndReplace {
+        target: u32,
+        unwind: Option<u32>,
+    },
+    Call {
+        func: String,
+        args: Vec<String>,
+        dest: Option<u32>,
+        cleanup: Option<u32>,
+        // Whether the call has a cleanup edge and the callee is not known not to unwind.
+        can_unwind: bool,
+        // Set when the callee is known statically.
+        callee: Option<MirDefId>,
+        // Type and const arguments of a statically known callee, including the
+        // ones of its impl, e.g. `[String]` for `Vec::<String>::push`.
+        substs: Vec<String>,
+        // For a callee with `#[rustc_legacy_const_generics]`, the positions in the
+        // source call of the arguments which were moved into its const generics.
+        // They are not in `args` but are the trailing consts of `substs`, in order.
+        // Calls written with explicit generic arguments, like `f::<3>(a)`, are not
+        // rewritten and have none. That is told from the source of the callee, so
+        // calls without one, e.g. from macros of other crates, are assumed to be
+        // rewritten.
+        legacy_const_args: Vec<u32>,
+        // Span of the callee expression, narrower than the span of the whole call.
+        fn_span: Option<MirSpan>,
+    },
+    // Explicit tail call (`become f(args)`), which returns from the body with the
+    // result of the call and so has no targets. Newer rustc have it; the MIR of
+    // this rustc has no such terminator, so only dumps written by an extractor
+    // built on a newer one contain it.
+    TailCall {
+        func: String,
+        args: Vec<String>,
+    },
+    Assert {
+        cond: String,
+        msg: MirAssertMessage,
+        target: u32,
+        cleanup: Option<u32>,
+        // Known for overflow checks of constants: `Some(false)` is a certain panic.
+        always_passes: Option<bool>,
+    },
+    Yield {
+        val: String,
+        resume: u32,
+        drop: Option<u32>,
+    },
+    GeneratorDrop,
+    FalseEdge {
+        real_target: u32,
+        imaginary_target: u32,
+    },
+    FalseUnwind {
+        real_target: u32,
+        unwind: Option<u32>,
+    },
+    InlineAsm {
+        dest: Option<u32>,
+    },
+    // The block has no terminator, which happens in MIR left incomplete by an
+    // error, or its terminator was cut off by `slice_between`.
+    Missing,
+}
+
+// One arm of a `SwitchInt`. `value` is `None` for the `otherwise` arm.
+// When switching on the discriminant of an enum, `variant` names the variant
+// the value stands for, and is `_` for the `otherwise` arm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirSwitchArm {
+    pub value: Option<u128>,
+    // `value` read as a value of the `switch_ty` of the switch: `false` or
+    // `true` for `bool`, the quoted character for `char`, and the signed value
+    // for signed integers. Integers are written out in full, so consumers whose
+    // JSON numbers are doubles (JavaScript) should read 128-bit and other values
+    // beyond 2^53 from here rather than from `value`.
+    pub rendered: Option<String>,
+    pub target: u32,
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MirAssertMessage {
+    // `index < len`. The values are set for operands which are constants.
+    BoundsCheck {
+        len: String,
+        index: String,
+        len_value: Option<u64>,
+        index_value: Option<u64>,
+    },
+    // Any other assertion, rendered as text.
+    Other(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MirStatement {
+    Assign {
+        place: String,
+        rvalue: MirRvalue,
+        // `place` decomposed into its access path.
+        place_path: MirPlace,
+        // Access paths of the places the rvalue reads or borrows.
+        operand_paths: Vec<MirPlace>,
+    },
+    // Reads inserted for borrowck, e.g. of a match scrutinee or a let binding.
+    FakeRead {
+        cause: String,
+        place: String,
+    },
+    // Marks `place` as uninitialized, e.g. before an aggregate is built in it
+    // field by field.
+    Deinit {
+        place: String,
+    },
+    // A coverage point, from `-Cinstrument-coverage`. `counter` is the counter or
+    // expression, e.g. `Counter(0)` or `Expression(4294967295) = 1 + 0`.
+    Coverage {
+        counter: String,
+        region: Option<MirSpan>,
+    },
+    // Any statement without a structured form, rendered as text.
+    Other(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MirRvalue {
+    // `operand as to_ty`. `kind` is the rustc `CastKind`, except that numeric
+    // casts are named `IntToInt`, `IntToFloat`, `FloatToInt` or `FloatToFloat`.
+    Cast {
+        kind: String,
+        operand: String,
+        from_ty: String,
+        to_ty: String,
+    },
+    // `&place`, `&mut place` and the borrows only seen by borrowck. `borrow_kind`
+    // is `Shared`, `Mut`, `Shallow` or `Unique`. The access path of `place` is the
+    // operand path of the assignment.
+    Ref {
+        borrow_kind: String,
+        place: String,
+    },
+    // Length of the array or slice `place`. For indexing, the assigned place is
+    // then the `len` operand of the `BoundsCheck` assertion.
+    Len {
+        place: String,
+    },
+    // Copy of the pointer `place` right before it is dereferenced. Newer rustc
+    // lower some derefs this way; the MIR of this rustc has no such rvalue, so
+    // only dumps written by an extractor built on a newer one contain it.
+    CopyForDeref {
+        place: String,
+    },
+    // `SizeOf` or `AlignOf` of `ty`.
+    NullaryOp {
+        op: String,
+        ty: String,
+    },
+    // Turns the pointer `operand` into a `Box<ty>`, as in the lowering of `box`.
+    ShallowInitBox {
+        operand: String,
+        ty: String,
+    },
+    // Any rvalue without a structured form, rendered as text.
+    Other(String),
+}
+
+// A place as an access path: a base local and the projections applied to it,
+// e.g. `(*_1).0` is `{ local: 1, projection: [Deref, Field(0)] }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MirPlace {
+    pub local: u32,
+    pub projection: Vec<MirProjectionElem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MirProjectionElem {
+    Deref,
+    Field(u32),
+    // The local holding the index.
+    Index(u32),
+    ConstantIndex {
+        offset: u64,
+        min_length: u64,
+        from_end: bool,
+    },
+    Subslice {
+        from: u64,
+        to: u64,
+        from_end: bool,
+    },
+    // Index of the enum variant.
+    Downcast(u32),
+}
+
+impl MirDefId {
+    // The id as one string, `crate:index`, or `crate:index::promoted[N]` for a
+    // promoted, e.g. `mycrate:42::promoted[1]`. Closures and generators have def
+    // indices of their own. This is the canonical identity of a body across the
+    // artifacts of a dump: split body files are named after it (see
+    // `file_stem_of_key`), and the call graph and string usage refer to bodies
+    // by it. Like def indices, keys are only stable within one build. Strings
+    // sort `10` before `9`; sort ids for numeric order.
+    pub fn key(&self) -> String {
+        match self.promoted {
+            Some(promoted) => {
+                format!("{}:{}::promoted[{}]", self.crate_name, self.def_index, promoted)
+            }
+            None => format!("{}:{}", self.crate_name, self.def_index),
+        }
+    }
+}
+
+// The `MirDefId::key` `key` as a file name stem, `crate.index` or
+// `crate.index.promoted.N`, as `:` and brackets are not allowed in file names
+// everywhere. Crate names have no dots, so `key_of_file_stem` reads it back.
+pub fn file_stem_of_key(key: &str) -> String {
+    key.replacen("::promoted[", ".promoted.", 1).replace(']', "").replacen(':', ".", 1)
+}
+
+// The key of a stem made by `file_stem_of_key`, `None` for any other stem.
+pub fn key_of_file_stem(stem: &str) -> Option<String> {
+    let (crate_name, rest) = stem.split_once('.')?;
+    let (def_index, promoted) = match rest.split_once(".promoted.") {
+        Some((def_index, promoted)) => (def_index, Some(promoted)),
+        None => (rest, None),
+    };
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let is_crate_name =
+        !crate_name.is_empty() && crate_name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !is_crate_name || !is_number(def_index) || !promoted.map_or(true, is_number) {
+        return None;
+    }
+    Some(match promoted {
+        Some(promoted) => format!("{}:{}::promoted[{}]", crate_name, def_index, promoted),
+        None => format!("{}:{}", crate_name, def_index),
+    })
+}
+
+impl MirBody {
+    // Calls `f` on every `MirDefId` of the body: its own, the ones of its parent
+    // and container, of the bodies inlined into its scopes and of its callees.
+    pub fn map_def_ids(&mut self, mut f: impl FnMut(&mut MirDefId)) {
+        f(&mut self.def_id);
+        self.parent.iter_mut().chain(&mut self.container).for_each(&mut f);
+        self.scopes.iter_mut().filter_map(|scope| scope.inlined.as_mut()).for_each(&mut f);
+        for (_, bb) in &mut self.blocks {
+            if let MirTerminator::Call { callee: Some(callee), .. } = &mut bb.term {
+                f(callee);
+            }
+        }
+    }
+
+    // Checks that every edge of the terminators, switch arms included, goes to a
+    // block of the body, and lists those which do not. In a truncated body the
+    // edges past its last block go to blocks cut off by `max_blocks` and are fine.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let blocks = self.blocks.iter().map(|(idx, _)| *idx).collect::<BTreeSet<_>>();
+        let last = blocks.iter().next_back().copied();
+        let is_valid = |target: u32| {
+            blocks.contains(&target) || (self.truncated && last.map_or(true, |last| target > last))
+        };
+        let mut violations = Vec::new();
+        for (idx, bb) in &self.blocks {
+            let term = bb.term();
+            let mut targets = term.successors().into_iter().map(|(t, _)| t).collect::<Vec<_>>();
+            if let MirTerminator::SwitchInt { arms, .. } = term {
+                targets.extend(arms.iter().map(|arm| arm.target));
+            }
+            for target in targets.into_iter().filter(|&target| !is_valid(target)) {
+                violations.push(format!("bb{}: {} to missing bb{}", idx, term.kind_name(), target));
+            }
+        }
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+}
+
+impl MirBasicBlock {
+    pub fn new(
+        statements: Vec<MirStatement>,
+        term: MirTerminator,
+        is_cleanup: bool,
+        reachable: bool,
+        is_trivial: bool,
+        ref_strs: Vec<String>,
+    ) -> Self {
+        let mut flags = 0;
+        if is_cleanup {
+            flags |= BB_IS_CLEANUP;
+        }
+        if reachable {
+            flags |= BB_REACHABLE;
+        }
+        if is_trivial {
+            flags |= BB_IS_TRIVIAL;
+        }
+        match term {
+            MirTerminator::Call { .. } => flags |= BB_HAS_CALL,
+            MirTerminator::TailCall { .. } => flags |= BB_HAS_CALL | BB_IS_EXIT,
+            MirTerminator::Assert { .. } => flags |= BB_HAS_ASSERT,
+            MirTerminator::Return => flags |= BB_IS_EXIT,
+            MirTerminator::Resume | MirTerminator::Abort => flags |= BB_IS_UNWIND_EXIT,
+            MirTerminator::Unreachable => flags |= BB_IS_UNREACHABLE,
+            _ => {}
+        }
+        if !ref_strs.is_empty() {
+            flags |= BB_HAS_STRING_REF;
+        }
+        MirBasicBlock {
+            statements,
+            term,
+            flags,
+            ref_strs,
+            stmt_strs: Vec::new(),
+            call_arg_strs: Vec::new(),
+            ref_str_ids: Vec::new(),
+            stmt_str_ids: Vec::new(),
+            call_arg_str_ids: Vec::new(),
+            const_operand_count: 0,
+            copy_operand_count: 0,
+            move_operand_count: 0,
+            statements_truncated: 0,
+            nop_count: 0,
+            float_consts: Vec::new(),
+            invalid_utf8_strs: Vec::new(),
+            statement_spans: Vec::new(),
+            statement_scopes: Vec::new(),
+        }
+    }
+}
+
+// Accessors for consumers deserializing the dump.
+impl MirBasicBlock {
+    pub fn statements(&self) -> &[MirStatement] {
+        &self.statements
+    }
+
+    pub fn term(&self) -> &MirTerminator {
+        &self.term
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    pub fn ref_str_ids(&self) -> &[u32] {
+        &self.ref_str_ids
+    }
+
+    pub fn is_cleanup(&self) -> bool {
+        self.flags & BB_IS_CLEANUP != 0
+    }
+
+    pub fn reachable(&self) -> bool {
+        self.flags & BB_REACHABLE != 0
+    }
+
+    pub fn has_call(&self) -> bool {
+        self.flags & BB_HAS_CALL != 0
+    }
+
+    pub fn has_assert(&self) -> bool {
+        self.flags & BB_HAS_ASSERT != 0
+    }
+
+    pub fn has_string_ref(&self) -> bool {
+        self.flags & BB_HAS_STRING_REF != 0
+    }
+
+    pub fn is_exit(&self) -> bool {
+        self.flags & BB_IS_EXIT != 0
+    }
+
+    pub fn is_unwind_exit(&self) -> bool {
+        self.flags & BB_IS_UNWIND_EXIT != 0
+    }
+
+    pub fn is_unreachable_terminator(&self) -> bool {
+        self.flags & BB_IS_UNREACHABLE != 0
+    }
+
+    pub fn is_trivial(&self) -> bool {
+        self.flags & BB_IS_TRIVIAL != 0
+    }
+
+    // Calls `f` on every string of the block, see `stmt_strs`.
+    pub fn for_each_str_mut(&mut self, mut f: impl FnMut(&mut String)) {
+        self.ref_strs.iter_mut().for_each(&mut f);
+        self.stmt_strs.iter_mut().for_each(&mut f);
+        self.call_arg_strs.iter_mut().for_each(&mut f);
+        self.invalid_utf8_strs.iter_mut().for_each(&mut f);
+    }
+
+    // Calls `f` on every rendered operand of the statements and terminator of
+    // the block, where constants are written out, e.g. `const "s"`. Places, types
+    // and other names are left out.
+    pub fn for_each_operand_text_mut(&mut self, mut f: impl FnMut(&mut String)) {
+        for stmt in &mut self.statements {
+            stmt.for_each_operand_text_mut(&mut f);
+        }
+        self.term.for_each_operand_text_mut(&mut f);
+    }
+
+    // See `MirTerminator::map_targets`.
+    pub fn map_targets(&mut self, f: impl Fn(u32) -> u32) {
+        self.term.map_targets(f);
+    }
+
+    // See `MirTerminator::fold_unreachable`.
+    pub fn fold_unreachable(&mut self, unreachable: impl Fn(u32) -> bool) {
+        self.term.fold_unreachable(unreachable);
+        if let MirTerminator::Unreachable = self.term {
+            self.flags |= BB_IS_UNREACHABLE;
+        }
+    }
+
+    // Moves the strings of the block to a string table, replacing each by the
+    // index `intern` returns for it.
+    pub fn intern_strs(&mut self, mut intern: impl FnMut(String) -> u32) {
+        self.ref_str_ids = mem::take(&mut self.ref_strs).into_iter().map(&mut intern).collect();
+        self.stmt_str_ids = mem::take(&mut self.stmt_strs).into_iter().map(&mut intern).collect();
+        self.call_arg_str_ids =
+            mem::take(&mut self.call_arg_strs).into_iter().map(&mut intern).collect();
+    }
+
+    // See `MirTerminator::retain_targets`.
+    pub fn retain_targets(&mut self, keep: impl Fn(u32) -> bool) {
+        self.term.retain_targets(keep);
+        if let MirTerminator::Missing = self.term {
+            self.flags &= !(BB_HAS_CALL | BB_HAS_ASSERT);
+        }
+    }
+}
+
+impl MirStatement {
+    // See `MirBasicBlock::for_each_operand_text_mut`. Rvalues and statements
+    // without a structured form are rendered as a whole.
+    pub fn for_each_operand_text_mut(&mut self, mut f: impl FnMut(&mut String)) {
+        match self {
+            MirStatement::Assign { rvalue, .. } => match rvalue {
+                MirRvalue::Cast { operand, .. } | MirRvalue::ShallowInitBox { operand, .. } => {
+                    f(operand)
+                }
+                MirRvalue::Other(text) => f(text),
+                MirRvalue::Ref { .. }
+                | MirRvalue::Len { .. }
+                | MirRvalue::CopyForDeref { .. }
+                | MirRvalue::NullaryOp { .. } => {}
+            },
+            MirStatement::Other(text) => f(text),
+            MirStatement::FakeRead { .. }
+            | MirStatement::Deinit { .. }
+            | MirStatement::Coverage { .. } => {}
+        }
+    }
+}
+
+impl MirTerminator {
+    // See `MirBasicBlock::for_each_operand_text_mut`.
+    pub fn for_each_operand_text_mut(&mut self, mut f: impl FnMut(&mut String)) {
+        match self {
+            MirTerminator::Call { func, args, .. } | MirTerminator::TailCall { func, args } => {
+                f(func);
+                args.iter_mut().for_each(f);
+            }
+            MirTerminator::Assert { cond, msg, .. } => {
+                f(cond);
+                match msg {
+                    MirAssertMessage::BoundsCheck { len, index, .. } => {
+                        f(len);
+                        f(index);
+                    }
+                    MirAssertMessage::Other(text) => f(text),
+                }
+            }
+            MirTerminator::Yield { val, .. } => f(val),
+            _ => {}
+        }
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            MirTerminator::Goto { .. } => "Goto",
+            MirTerminator::SwitchInt { .. } => "SwitchInt",
+            MirTerminator::Resume => "Resume",
+            MirTerminator::Abort => "Abort",
+            MirTerminator::Return => "Return",
+            MirTerminator::Unreachable => "Unreachable",
+            MirTerminator::Drop { .. } => "Drop",
+            MirTerminator::DropAndReplace { .. } => "DropAndReplace",
+            MirTerminator::Call { .. } => "Call",
+            MirTerminator::TailCall { .. } => "TailCall",
+            MirTerminator::Assert { .. } => "Assert",
+            MirTerminator::Yield { .. } => "Yield",
+            MirTerminator::GeneratorDrop => "GeneratorDrop",
+            MirTerminator::FalseEdge { .. } => "FalseEdge",
+            MirTerminator::FalseUnwind { .. } => "FalseUnwind",
+            MirTerminator::InlineAsm { .. } => "InlineAsm",
+            MirTerminator::Missing => "Missing",
+        }
+    }
+
+    // Removes the edges to blocks for which `keep` is false. Optional targets are
+    // cleared, the terminator becomes `Missing` if a required target is removed.
+    pub fn retain_targets(&mut self, keep: impl Fn(u32) -> bool) {
+        let clear = |target: &mut Option<u32>| {
+            if target.map_or(false, |target| !keep(target)) {
+                *target = None;
+            }
+        };
+        let cut = match self {
+            MirTerminator::Goto { target } => !keep(*target),
+            MirTerminator::SwitchInt { targets, arms, .. } => {
+                targets.retain(|target| keep(*target));
+                arms.retain(|arm| keep(arm.target));
+                false
+            }
+            MirTerminator::Resume
+            | MirTerminator::Abort
+            | MirTerminator::Return
+            | MirTerminator::Unreachable
+            | MirTerminator::GeneratorDrop
+            | MirTerminator::TailCall { .. }
+            | MirTerminator::Missing => false,
+            MirTerminator::Drop { target, unwind }
+            | MirTerminator::DropAndReplace { target, unwind }
+            | MirTerminator::FalseUnwind { real_target: target, unwind } => {
+                clear(unwind);
+                !keep(*target)
+            }
+            MirTerminator::Call { dest, cleanup, .. } => {
+                clear(dest);
+                clear(cleanup);
+                false
+            }
+            MirTerminator::Assert { target, cleanup, .. } => {
+                clear(cleanup);
+                !keep(*target)
+            }
+            MirTerminator::Yield { resume, drop, .. } => {
+                clear(drop);
+                !keep(*resume)
+            }
+            MirTerminator::FalseEdge { real_target, imaginary_target } => {
+                !keep(*real_target) || !keep(*imaginary_target)
+            }
+            MirTerminator::InlineAsm { dest } => {
+                clear(dest);
+                false
+            }
+        };
+        if cut {
+            *self = MirTerminator::Missing;
+        }
+    }
+
+    // Replaces every target by `f` of it, the kind of each edge is unchanged.
+    pub fn map_targets(&mut self, f: impl Fn(u32) -> u32) {
+        let map = |target: &mut u32| *target = f(*target);
+        let map_opt = |target: &mut Option<u32>| target.iter_mut().for_each(map);
+        match self {
+            MirTerminator::Goto { target } => map(target),
+            MirTerminator::SwitchInt { targets, arms, .. } => {
+                targets.iter_mut().for_each(map);
+                arms.iter_mut().for_each(|arm| map(&mut arm.target));
+            }
+            MirTerminator::Resume
+            | MirTerminator::Abort
+            | MirTerminator::Return
+            | MirTerminator::Unreachable
+            | MirTerminator::GeneratorDrop
+            | MirTerminator::TailCall { .. }
+            | MirTerminator::Missing => {}
+            MirTerminator::Drop { target, unwind }
+            | MirTerminator::DropAndReplace { target, unwind }
+            | MirTerminator::Assert { target, cleanup: unwind, .. }
+            | MirTerminator::FalseUnwind { real_target: target, unwind } => {
+                map(target);
+                map_opt(unwind);
+            }
+            MirTerminator::Call { dest, cleanup, .. } => {
+                map_opt(dest);
+                map_opt(cleanup);
+            }
+            MirTerminator::Yield { resume, drop, .. } => {
+                map(resume);
+                map_opt(drop);
+            }
+            MirTerminator::FalseEdge { real_target, imaginary_target } => {
+                map(real_target);
+                map(imaginary_target);
+            }
+            MirTerminator::InlineAsm { dest } => map_opt(dest),
+        }
+    }
+
+    // Removes the edges to blocks for which `unreachable` is true where the edge
+    // is optional: switch value arms, call returns and unwind edges. A call
+    // without return edge diverges. The `otherwise` target of a switch is kept
+    // last in `targets`, as in rustc, unless it is unreachable as well as all the
+    // value arms: then the switch becomes `Unreachable` itself. Required targets,
+    // like the one of a `Goto` or the success target of an `Assert`, are kept.
+    pub fn fold_unreachable(&mut self, unreachable: impl Fn(u32) -> bool) {
+        let clear = |target: &mut Option<u32>| {
+            if target.map_or(false, &unreachable) {
+                *target = None;
+            }
+        };
+        match self {
+            MirTerminator::SwitchInt { targets, arms, .. } => {
+                let otherwise = targets.pop();
+                targets.retain(|target| !unreachable(*target));
+                arms.retain(|arm| arm.value.is_none() || !unreachable(arm.target));
+                match otherwise {
+                    Some(otherwise) if !targets.is_empty() || !unreachable(otherwise) => {
+                        targets.push(otherwise)
+                    }
+                    _ => *self = MirTerminator::Unreachable,
+                }
+            }
+            MirTerminator::Drop { unwind, .. }
+            | MirTerminator::DropAndReplace { unwind, .. }
+            | MirTerminator::FalseUnwind { unwind, .. } => clear(unwind),
+            MirTerminator::Call { dest, cleanup, .. } => {
+                clear(dest);
+                clear(cleanup);
+            }
+            MirTerminator::Assert { cleanup, .. } => clear(cleanup),
+            MirTerminator::Yield { drop, .. } => clear(drop),
+            MirTerminator::InlineAsm { dest } => clear(dest),
+            MirTerminator::Goto { .. }
+            | MirTerminator::Resume
+            | MirTerminator::Abort
+            | MirTerminator::Return
+            | MirTerminator::Unreachable
+            | MirTerminator::GeneratorDrop
+            | MirTerminator::FalseEdge { .. }
+            | MirTerminator::TailCall { .. }
+            | MirTerminator::Missing => {}
+        }
+    }
+
+    // Successor blocks, each with whether the edge is taken on unwinding.
+    pub fn successors(&self) -> Vec<(u32, bool)> {
+        let normal = |target: &u32| (*target, false);
+        let unwind = |target: &u32| (*target, true);
+        match self {
+            MirTerminator::Goto { target } => vec![normal(target)],
+            MirTerminator::SwitchInt { targets, .. } => targets.iter().map(normal).collect(),
+            MirTerminator::Resume
+            | MirTerminator::Abort
+            | MirTerminator::Return
+            | MirTerminator::Unreachable
+            | MirTerminator::GeneratorDrop
+            | MirTerminator::TailCall { .. }
+            | MirTerminator::Missing => vec![],
+            MirTerminator::Drop { target, unwind: cleanup }
+            | MirTerminator::DropAndReplace { target, unwind: cleanup }
+            | MirTerminator::Assert { target, cleanup, .. }
+            | MirTerminator::FalseUnwind { real_target: target, unwind: cleanup } => {
+                std::iter::once(normal(target)).chain(cleanup.iter().map(unwind)).collect()
+            }
+            MirTerminator::Call { dest, cleanup, .. } => {
+                dest.iter().map(normal).chain(cleanup.iter().map(unwind)).collect()
+            }
+            MirTerminator::Yield { resume, drop, .. } => {
+                std::iter::once(normal(resume)).chain(drop.iter().map(normal)).collect()
+            }
+            MirTerminator::FalseEdge { real_target, imaginary_target } => {
+                vec![normal(real_target), normal(imaginary_target)]
+            }
+            MirTerminator::InlineAsm { dest } => dest.iter().map(normal).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,118 @@
+use crate::mir_dump::body_to_text;
+use crate::mir_dump::tests::{block, body, call, def_id};
+use crate::mir_dump::types::{
+    file_stem_of_key, key_of_file_stem, MirBody, MirScope, MirSwitchArm, MirTerminator,
+};
+
+#[test]
+fn validate_accepts_existing_targets() {
+    let body = body(
+        1,
+        "krate::foo",
+        vec![block(MirTerminator::Goto { target: 1 }), block(MirTerminator::Return)],
+    );
+    assert_eq!(body.validate(), Ok(()));
+}
+
+#[test]
+fn validate_reports_missing_targets() {
+    let mut body = body(
+        1,
+        "krate::foo",
+        vec![block(MirTerminator::Goto { target: 3 }), block(MirTerminator::Return)],
+    );
+    assert_eq!(body.validate(), Err(vec!["bb0: Goto to missing bb3".to_string()]));
+    // Blocks past the last one were cut off by `max_blocks`.
+    body.truncated = true;
+    assert_eq!(body.validate(), Ok(()));
+}
+
+#[test]
+fn validate_checks_switch_arms() {
+    let body = body(
+        1,
+        "krate::foo",
+        vec![
+            block(MirTerminator::SwitchInt {
+                switch_ty: "bool".to_string(),
+                targets: vec![1],
+                arms: vec![MirSwitchArm { value: Some(0), rendered: None, target: 2, variant: None }],
+            }),
+            block(MirTerminator::Return),
+        ],
+    );
+    assert_eq!(body.validate(), Err(vec!["bb0: SwitchInt to missing bb2".to_string()]));
+}
+
+#[test]
+fn key_of_bodies_and_promoteds() {
+    let mut id = def_id(42);
+    assert_eq!(id.key(), "krate:42");
+    id.promoted = Some(1);
+    assert_eq!(id.key(), "krate:42::promoted[1]");
+}
+
+#[test]
+fn map_def_ids_visits_every_id() {
+    let mut body = body(1, "krate::foo", vec![block(call("bar", Some(def_id(2)), None))]);
+    body.parent = Some(def_id(3));
+    body.container = Some(def_id(4));
+    body.scopes = vec![MirScope { parent: None, inlined: Some(def_id(5)) }];
+    let mut indices = Vec::new();
+    body.map_def_ids(|id| indices.push(id.def_index));
+    assert_eq!(indices, vec![1, 3, 4, 5, 2]);
+}
+
+#[test]
+fn file_stems_of_keys() {
+    let mut id = def_id(42);
+    assert_eq!(file_stem_of_key(&id.key()), "krate.42");
+    assert_eq!(key_of_file_stem("krate.42"), Some(id.key()));
+    id.promoted = Some(1);
+    assert_eq!(file_stem_of_key(&id.key()), "krate.42.promoted.1");
+    assert_eq!(key_of_file_stem("krate.42.promoted.1"), Some(id.key()));
+    // Files the dumper did not write.
+    for stem in ["notes", "krate.x", "krate.42.promoted.", "krate.42.old", ".42", "my-crate.1"] {
+        assert_eq!(key_of_file_stem(stem), None, "{}", stem);
+    }
+}
+
+// Blocks left without a terminator by an error, see `MirTerminator::Missing`.
+// rustc stops before analysis on such errors, so this is only built by hand.
+#[test]
+fn missing_terminator() {
+    let body = body(
+        1,
+        "krate::foo",
+        vec![block(MirTerminator::Goto { target: 1 }), block(MirTerminator::Missing)],
+    );
+    assert!(body.blocks[1].1.term().successors().is_empty());
+    assert_eq!(body.validate(), Ok(()));
+    assert!(body_to_text(&body).contains("        // no terminator;\n"));
+    let json = serde_json::to_string(&body).unwrap();
+    let body: MirBody = serde_json::from_str(&json).unwrap();
+    assert!(matches!(body.blocks[1].1.term(), MirTerminator::Missing));
+}
+
+// 128-bit switch values are JSON numbers beyond the range of doubles, see
+// `MirSwitchArm::rendered`.
+#[test]
+fn switch_values_round_trip_exactly() {
+    let arm = MirSwitchArm {
+        value: Some(i128::MIN as u128),
+        rendered: Some(i128::MIN.to_string()),
+        target: 1,
+        variant: None,
+    };
+    let json = serde_json::to_string(&arm).unwrap();
+    assert_eq!(
+        json,
+        r#"{"value":170141183460469231731687303715884105728,"rendered":"-170141183460469231731687303715884105728","target":1,"variant":null}"#
+    );
+    let arm: MirSwitchArm = serde_json::from_str(&json).unwrap();
+    assert_eq!(arm.value, Some(i128::MIN as u128));
+
+    let json = serde_json::to_string(&MirSwitchArm { value: Some(u128::MAX), ..arm }).unwrap();
+    let arm: MirSwitchArm = serde_json::from_str(&json).unwrap();
+    assert_eq!(arm.value, Some(u128::MAX));
+}
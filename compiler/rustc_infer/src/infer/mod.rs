@@ -21,6 +21,7 @@
 use rustc_middle::infer::unify_key::{ConstVarValue, ConstVariableValue};
 use rustc_middle::infer::unify_key::{ConstVariableOrigin, ConstVariableOriginKind, ToType};
 use rustc_middle::mir::interpret::{ErrorHandled, EvalToConstValueResult};
+use rustc_middle::traits::query::OutlivesBound;
 use rustc_middle::traits::select;
 use rustc_middle::ty::error::{ExpectedFound, TypeError};
 use rustc_middle::ty::fold::{TypeFoldable, TypeFolder, TypeSuperFoldable};
@@ -295,6 +296,13 @@ pub struct InferCtxt<'a, 'tcx> {
 
     pub reported_closure_mismatch: RefCell<FxHashSet<(Span, Option<Span>)>>,
 
+    /// Caches the implied outlives bounds of types, keyed by the (interned)
+    /// param env and type they were computed for. The bounds may refer to
+    /// region variables of this inference context, so the cache must not
+    /// outlive it and is not shared with other contexts.
+    pub implied_bounds_cache:
+        RefCell<FxHashMap<(ty::ParamEnv<'tcx>, Ty<'tcx>), Vec<OutlivesBound<'tcx>>>>,
+
     /// When an error occurs, we want to avoid reporting "derived"
     /// errors that are due to this original failure. Normally, we
     /// handle this with the `err_count_on_creation` count, which
@@ -611,6 +619,7 @@ pub fn enter<R>(&mut self, f: impl for<'a> FnOnce(InferCtxt<'a, 'tcx>) -> R) ->
             evaluation_cache: Default::default(),
             reported_trait_errors: Default::default(),
             reported_closure_mismatch: Default::default(),
+            implied_bounds_cache: Default::default(),
             tainted_by_errors_flag: Cell::new(false),
             err_count_on_creation: tcx.sess.err_count(),
             in_snapshot: Cell::new(false),
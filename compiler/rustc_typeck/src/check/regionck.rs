@@ -137,11 +137,11 @@ fn add_implied_bounds<'a>(
         body_id: hir::HirId,
         span: Span,
     ) {
-        for ty in fn_sig_tys {
-            let ty = infcx.resolve_vars_if_possible(ty);
-            let implied_bounds = infcx.implied_outlives_bounds(self.param_env, body_id, ty, span);
-            self.add_outlives_bounds(Some(infcx), implied_bounds)
-        }
+        let tys: Vec<_> =
+            fn_sig_tys.into_iter().map(|ty| infcx.resolve_vars_if_possible(ty)).collect();
+        let implied_bounds =
+            infcx.implied_outlives_bounds_many(self.param_env, body_id, &tys, span);
+        self.add_outlives_bounds(Some(infcx), implied_bounds)
     }
 }
 
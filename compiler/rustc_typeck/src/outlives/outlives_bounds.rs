@@ -1,5 +1,6 @@
+use rustc_data_structures::fx::FxIndexSet;
 use rustc_hir as hir;
-use rustc_middle::ty::{self, Ty};
+use rustc_middle::ty::{self, Ty, TypeFoldable};
 use rustc_span::source_map::Span;
 use rustc_trait_selection::infer::InferCtxt;
 use rustc_trait_selection::traits::query::type_op::{self, TypeOp, TypeOpOutput};
@@ -16,6 +17,30 @@ fn implied_outlives_bounds(
         ty: Ty<'tcx>,
         span: Span,
     ) -> Vec<OutlivesBound<'tcx>>;
+
+    fn implied_outlives_bounds_many(
+        &self,
+        param_env: ty::ParamEnv<'tcx>,
+        body_id: hir::HirId,
+        tys: &[Ty<'tcx>],
+        span: Span,
+    ) -> Vec<OutlivesBound<'tcx>>;
+
+    fn implied_outlives_bounds_with_obligations(
+        &self,
+        param_env: ty::ParamEnv<'tcx>,
+        body_id: hir::HirId,
+        ty: Ty<'tcx>,
+        span: Span,
+    ) -> (Vec<OutlivesBound<'tcx>>, usize);
+
+    fn implied_outlives_bounds_for_sig(
+        &self,
+        param_env: ty::ParamEnv<'tcx>,
+        body_id: hir::HirId,
+        sig: &ty::FnSig<'tcx>,
+        span: Span,
+    ) -> Vec<(Ty<'tcx>, Vec<OutlivesBound<'tcx>>)>;
 }
 
 impl<'cx, 'tcx> InferCtxtExt<'tcx> for InferCtxt<'cx, 'tcx> {
@@ -48,17 +73,108 @@ fn implied_outlives_bounds(
         ty: Ty<'tcx>,
         span: Span,
     ) -> Vec<OutlivesBound<'tcx>> {
+        self.implied_outlives_bounds_many(param_env, body_id, &[ty], span)
+    }
+
+    /// Like `implied_outlives_bounds`, but for a whole slice of types at
+    /// once (e.g., all the argument types of a function). The constraints
+    /// produced by instantiating the query results are registered in a
+    /// single `FulfillmentContext` and solved together, and the returned
+    /// bounds are deduplicated, keeping the order in which they were
+    /// first produced.
+    ///
+    /// Results are cached in `InferCtxt::implied_bounds_cache`, so asking again
+    /// for a type within the same inference context does not re-run the type
+    /// op. Types with inference variables are not cached, as they may be
+    /// resolved differently later, and neither are results computed in a
+    /// snapshot, whose constraints may be rolled back.
+    #[instrument(level = "debug", skip(self, param_env, body_id, span))]
+    fn implied_outlives_bounds_many(
+        &self,
+        param_env: ty::ParamEnv<'tcx>,
+        body_id: hir::HirId,
+        tys: &[Ty<'tcx>],
+        span: Span,
+    ) -> Vec<OutlivesBound<'tcx>> {
+        dedup_bounds(implied_outlives_bounds_in(self, param_env, body_id, tys, span).0)
+    }
+
+    /// Like `implied_outlives_bounds`, but also returns the number of outlives
+    /// obligations that instantiating the query result registered, and that
+    /// were then processed into region constraints of the inference context.
+    /// If it is not zero, the caller may have to re-run fulfillment. Bounds
+    /// taken from the cache register no obligations.
+    #[instrument(level = "debug", skip(self, param_env, body_id, span))]
+    fn implied_outlives_bounds_with_obligations(
+        &self,
+        param_env: ty::ParamEnv<'tcx>,
+        body_id: hir::HirId,
+        ty: Ty<'tcx>,
+        span: Span,
+    ) -> (Vec<OutlivesBound<'tcx>>, usize) {
+        let (bounds, obligations) =
+            implied_outlives_bounds_in(self, param_env, body_id, &[ty], span);
+        (dedup_bounds(bounds), obligations)
+    }
+
+    /// The implied bounds of each argument type of `sig` and of its return
+    /// type, in that order, computed as by `implied_outlives_bounds_many` with
+    /// a single `FulfillmentContext`. The bounds are grouped by the type they
+    /// come from and are not deduplicated across types; a type appearing more
+    /// than once in `sig` is listed each time.
+    #[instrument(level = "debug", skip(self, param_env, body_id, span))]
+    fn implied_outlives_bounds_for_sig(
+        &self,
+        param_env: ty::ParamEnv<'tcx>,
+        body_id: hir::HirId,
+        sig: &ty::FnSig<'tcx>,
+        span: Span,
+    ) -> Vec<(Ty<'tcx>, Vec<OutlivesBound<'tcx>>)> {
+        implied_outlives_bounds_in(self, param_env, body_id, &sig.inputs_and_output, span).0
+    }
+}
+
+/// The bounds of all types, each once, in the order they were first produced.
+fn dedup_bounds<'tcx>(
+    bounds: Vec<(Ty<'tcx>, Vec<OutlivesBound<'tcx>>)>,
+) -> Vec<OutlivesBound<'tcx>> {
+    let bounds: FxIndexSet<_> = bounds.into_iter().flat_map(|(_, bounds)| bounds).collect();
+    bounds.into_iter().collect()
+}
+
+/// The implied bounds of each of `tys`, see `implied_outlives_bounds_many`, with
+/// the number of outlives obligations registered while computing them. Types
+/// for which the type op fails have no bounds.
+fn implied_outlives_bounds_in<'cx, 'tcx>(
+    infcx: &InferCtxt<'cx, 'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    body_id: hir::HirId,
+    tys: &[Ty<'tcx>],
+    span: Span,
+) -> (Vec<(Ty<'tcx>, Vec<OutlivesBound<'tcx>>)>, usize) {
+    let mut bounds = Vec::with_capacity(tys.len());
+    let mut obligations = 0;
+    let mut fulfill_cx = FulfillmentContext::new();
+    let cause = ObligationCause::misc(span, body_id);
+
+    for &ty in tys {
+        if let Some(cached) = infcx.implied_bounds_cache.borrow().get(&(param_env, ty)) {
+            bounds.push((ty, cached.clone()));
+            continue;
+        }
+
         let result = param_env
             .and(type_op::implied_outlives_bounds::ImpliedOutlivesBounds { ty })
-            .fully_perform(self);
+            .fully_perform(infcx);
         let result = match result {
             Ok(r) => r,
             Err(NoSolution) => {
-                self.tcx.sess.delay_span_bug(
+                infcx.tcx.sess.delay_span_bug(
                     span,
                     "implied_outlives_bounds failed to solve all obligations",
                 );
-                return vec![];
+                bounds.push((ty, vec![]));
+                continue;
             }
         };
 
@@ -67,28 +183,33 @@ fn implied_outlives_bounds(
         if let Some(constraints) = constraints {
             // Instantiation may have produced new inference variables and constraints on those
             // variables. Process these constraints.
-            let mut fulfill_cx = FulfillmentContext::new();
-            let cause = ObligationCause::misc(span, body_id);
             for &constraint in &constraints.outlives {
-                let obligation = self.query_outlives_constraint_to_obligation(
+                let obligation = infcx.query_outlives_constraint_to_obligation(
                     constraint,
                     cause.clone(),
                     param_env,
                 );
-                fulfill_cx.register_predicate_obligation(self, obligation);
+                fulfill_cx.register_predicate_obligation(infcx, obligation);
+                obligations += 1;
             }
             if !constraints.member_constraints.is_empty() {
                 span_bug!(span, "{:#?}", constraints.member_constraints);
             }
-            let errors = fulfill_cx.select_all_or_error(self);
-            if !errors.is_empty() {
-                self.tcx.sess.delay_span_bug(
-                    span,
-                    "implied_outlives_bounds failed to solve obligations from instantiation",
-                );
-            }
-        };
+        }
 
-        output
+        if !ty.needs_infer() && !infcx.is_in_snapshot() {
+            infcx.implied_bounds_cache.borrow_mut().insert((param_env, ty), output.clone());
+        }
+        bounds.push((ty, output));
     }
+
+    let errors = fulfill_cx.select_all_or_error(infcx);
+    if !errors.is_empty() {
+        infcx.tcx.sess.delay_span_bug(
+            span,
+            "implied_outlives_bounds failed to solve obligations from instantiation",
+        );
+    }
+
+    (bounds, obligations)
 }
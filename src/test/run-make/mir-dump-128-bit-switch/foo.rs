@@ -0,0 +1,13 @@
+pub fn unsigned(x: u128) -> &'static str {
+    match x {
+        u128::MAX => "max",
+        _ => "other",
+    }
+}
+
+pub fn signed(x: i128) -> &'static str {
+    match x {
+        i128::MIN => "min",
+        _ => "other",
+    }
+}
@@ -0,0 +1,10 @@
+pub struct Foo {
+    pub s: &'static str,
+    pub n: u32,
+}
+
+const C: Foo = Foo { s: "x", n: 1 };
+
+pub fn get() -> Foo {
+    C
+}
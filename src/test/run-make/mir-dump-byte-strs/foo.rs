@@ -0,0 +1,3 @@
+pub fn bytes() -> &'static [u8; 5] {
+    b"bytes"
+}
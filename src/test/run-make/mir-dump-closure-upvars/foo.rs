@@ -0,0 +1,8 @@
+pub fn run(x: u32, y: String) -> usize {
+    let c = || {
+        let total = x as usize + y.len();
+        drop(y);
+        total
+    };
+    c()
+}
@@ -0,0 +1,5 @@
+const fn double(x: u32) -> u32 {
+    x * 2
+}
+
+pub const FOUR: u32 = double(2);
@@ -0,0 +1,8 @@
+#[derive(Debug)]
+pub struct Point {
+    pub x: u32,
+}
+
+pub fn origin() -> Point {
+    Point { x: 0 }
+}
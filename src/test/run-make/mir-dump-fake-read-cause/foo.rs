@@ -0,0 +1,6 @@
+pub fn guarded(x: Option<u32>) -> u32 {
+    match x {
+        Some(n) if n > 1 => n,
+        _ => 0,
+    }
+}
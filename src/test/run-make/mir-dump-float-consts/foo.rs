@@ -0,0 +1,11 @@
+pub fn nan() -> f32 {
+    f32::NAN
+}
+
+pub fn infinity() -> f64 {
+    f64::INFINITY
+}
+
+pub fn normal() -> f64 {
+    1.5
+}
@@ -0,0 +1,3 @@
+pub fn narrow(x: u32) -> u8 {
+    x as u8
+}
@@ -0,0 +1,5 @@
+const BAD: &str = unsafe { std::str::from_utf8_unchecked(b"a\xffb") };
+
+pub fn bad() -> &'static str {
+    BAD
+}
@@ -0,0 +1,3 @@
+pub fn get(s: &[u32], i: usize) -> u32 {
+    s[i]
+}
@@ -0,0 +1,3 @@
+pub fn pair<T>(a: u64, b: T) -> (u64, T) {
+    (a, b)
+}
@@ -0,0 +1,8 @@
+#[repr(transparent)]
+pub struct Name(pub &'static str);
+
+const N: Name = Name("bob");
+
+pub fn name() -> Name {
+    N
+}
@@ -0,0 +1,8 @@
+#![no_std]
+
+pub fn nonzero(x: u32) -> u32 {
+    if x == 0 {
+        panic!("zero");
+    }
+    x
+}
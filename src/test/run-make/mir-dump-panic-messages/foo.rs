@@ -0,0 +1,3 @@
+pub fn explode() {
+    panic!("boom");
+}
@@ -0,0 +1 @@
+pub static GREETING: &str = "hi";
@@ -0,0 +1,5 @@
+pub trait Greet {
+    fn greet(&self) {
+        println!("hi");
+    }
+}